@@ -0,0 +1,336 @@
+use super::*;
+use std::collections::BTreeMap;
+
+/// Options that the FAH client should have, loaded from JSON or TOML. Options that are not given
+/// are left unchanged. Values may be strings, numbers or booleans.
+///
+/// ```toml
+/// power = "FULL"
+/// cpu-usage = 80
+///
+/// [slots.1]
+/// paused = true
+/// ```
+///
+/// Example
+/// ```no_run
+/// let desired = fahapi::DesiredOptions::from_json(r#"{"power": "FULL"}"#).unwrap();
+/// let mut api = fahapi::API::connect_timeout(
+///     &fahapi::DEFAULT_ADDR,
+///     std::time::Duration::from_secs(1),
+/// ).unwrap();
+/// for change in desired.apply(&mut api, true).unwrap() {
+///     println!("{}", change);
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default, serde::Deserialize)]
+#[serde(from = "RawDesiredOptions")]
+pub struct DesiredOptions {
+    /// Client options by their kebab-case names
+    pub options: BTreeMap<String, String>,
+    /// Slot options by slot and their kebab-case names
+    pub slots: BTreeMap<SlotId, BTreeMap<String, String>>,
+}
+
+/// A change that `DesiredOptions` makes to an option.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Change {
+    /// None for client options
+    pub slot: Option<SlotId>,
+    pub key: String,
+    /// None if the current value of a slot option is unknown
+    pub old: Option<String>,
+    pub new: String,
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(slot) = self.slot {
+            write!(f, "slot {} ", slot)?;
+        }
+        match &self.old {
+            Some(old) => write!(f, "{}: {:?} -> {:?}", self.key, old, self.new),
+            None => write!(f, "{}: {:?}", self.key, self.new),
+        }
+    }
+}
+
+impl Change {
+    fn set(&self, api: &mut API, value: &str) -> Result<()> {
+        match self.slot {
+            Some(slot) => api.slot_options_set(slot, &self.key, value),
+            None => api.options_set(&self.key, value),
+        }
+    }
+}
+
+impl DesiredOptions {
+    pub fn from_json(s: &str) -> Result<Self> {
+        serde_json::from_str(s).map_err(|e| Error::Other(e.into()))
+    }
+
+    /// Enable the `toml` feature to use this function.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self> {
+        toml::from_str(s).map_err(|e| Error::Other(e.into()))
+    }
+
+    /// Returns the changes that are needed to make the client's options match. Client options
+    /// that are not fields of [`Options`](./struct.Options.html) are an error, to catch typos.
+    pub fn diff(&self, api: &mut API) -> Result<Vec<Change>> {
+        let mut changes = Vec::new();
+
+        if !self.options.is_empty() {
+            let current = to_strings(&api.options_get()?)?;
+            for (key, new) in &self.options {
+                let old = current
+                    .get(key)
+                    .ok_or_else(|| Error::InvalidCommand(format!("unknown option: {}", key)))?;
+                if old != new {
+                    changes.push(Change {
+                        slot: None,
+                        key: key.clone(),
+                        old: Some(old.clone()),
+                        new: new.clone(),
+                    });
+                }
+            }
+        }
+
+        for (slot, options) in &self.slots {
+            let current = to_strings(&api.slot_options_get(*slot)?)?;
+            for (key, new) in options {
+                let old = current.get(key);
+                if old != Some(new) {
+                    changes.push(Change {
+                        slot: Some(*slot),
+                        key: key.clone(),
+                        old: old.cloned(),
+                        new: new.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Sets the options that differ and returns the changes. Nothing is set if `dry_run` is true.
+    ///
+    /// If setting an option fails, the options that were already set are set back to their old
+    /// values, and the error is returned.
+    pub fn apply(&self, api: &mut API, dry_run: bool) -> Result<Vec<Change>> {
+        let changes = self.diff(api)?;
+        if !dry_run {
+            apply_changes(api, &changes)?;
+        }
+        Ok(changes)
+    }
+}
+
+/// Sets the new values in order. If one fails, the values that were already set are set back to
+/// their old values in reverse order, and the error is returned.
+pub(crate) fn apply_changes(api: &mut API, changes: &[Change]) -> Result<()> {
+    for (i, change) in changes.iter().enumerate() {
+        if let Err(e) = change.set(api, &change.new) {
+            let rollback = changes[..i]
+                .iter()
+                .rev()
+                .try_for_each(|applied| match &applied.old {
+                    Some(old) => applied.set(api, old),
+                    None => Ok(()),
+                });
+            return Err(match rollback {
+                Ok(()) => e,
+                Err(rollback_err) => Error::Other(
+                    format!("{}: {}; rollback failed: {}", change, e, rollback_err).into(),
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the fields of a struct that serializes to strings, such as `Options`.
+fn to_strings(value: &impl serde::Serialize) -> Result<BTreeMap<String, String>> {
+    let value = serde_json::to_value(value).map_err(|e| Error::Other(e.into()))?;
+    let map = match value {
+        serde_json::Value::Object(map) => map,
+        _ => return Err(Error::UnexpectedShape("expected an object".to_string())),
+    };
+    Ok(map
+        .into_iter()
+        .map(|(k, v)| match v {
+            serde_json::Value::String(s) => (k, s),
+            v => (k, v.to_string()),
+        })
+        .collect())
+}
+
+#[derive(serde::Deserialize)]
+struct RawDesiredOptions {
+    #[serde(flatten)]
+    options: BTreeMap<String, RawValue>,
+    #[serde(default)]
+    slots: BTreeMap<SlotId, BTreeMap<String, RawValue>>,
+}
+
+/// Value of an option, which is sent to the client as a string.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum RawValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl std::fmt::Display for RawValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RawValue::Bool(b) => write!(f, "{}", b),
+            RawValue::Int(n) => write!(f, "{}", n),
+            RawValue::Float(n) => write!(f, "{}", n),
+            RawValue::String(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<RawDesiredOptions> for DesiredOptions {
+    fn from(raw: RawDesiredOptions) -> Self {
+        let strings = |options: BTreeMap<String, RawValue>| {
+            options
+                .into_iter()
+                .map(|(k, v)| (k, v.to_string()))
+                .collect()
+        };
+        Self {
+            options: strings(raw.options),
+            slots: raw
+                .slots
+                .into_iter()
+                .map(|(slot, options)| (slot, strings(options)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_desired_options_parse() {
+        let mut expected = DesiredOptions {
+            options: [
+                ("cpu-usage", "80"),
+                ("power", "FULL"),
+                ("proxy-enable", "false"),
+            ]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+            slots: BTreeMap::new(),
+        };
+        expected
+            .slots
+            .entry(SlotId(1))
+            .or_default()
+            .insert("paused".to_string(), "true".to_string());
+
+        let json = r#"{
+            "power": "FULL",
+            "cpu-usage": 80,
+            "proxy-enable": false,
+            "slots": {"01": {"paused": true}}
+        }"#;
+        assert_eq!(DesiredOptions::from_json(json).unwrap(), expected);
+        assert!(DesiredOptions::from_json(r#"{"power": ["FULL"]}"#).is_err());
+
+        #[cfg(feature = "toml")]
+        {
+            let toml = r#"
+                power = "FULL"
+                cpu-usage = 80
+                proxy-enable = false
+
+                [slots.1]
+                paused = true
+            "#;
+            assert_eq!(DesiredOptions::from_toml(toml).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_desired_options_apply() {
+        let server = testing::MockServer::start().unwrap();
+        let mut api =
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+
+        let desired = DesiredOptions::from_json(
+            r#"{"cpu-usage": 80, "power": "MEDIUM", "slots": {"00": {"paused": true, "machine-id": 1}}}"#,
+        )
+        .unwrap();
+        let expected = vec![
+            Change {
+                slot: None,
+                key: "cpu-usage".to_string(),
+                old: Some("100".to_string()),
+                new: "80".to_string(),
+            },
+            Change {
+                slot: Some(SlotId(0)),
+                key: "machine-id".to_string(),
+                old: Some("0".to_string()),
+                new: "1".to_string(),
+            },
+            Change {
+                slot: Some(SlotId(0)),
+                key: "paused".to_string(),
+                old: Some("false".to_string()),
+                new: "true".to_string(),
+            },
+        ];
+        assert_eq!(expected[0].to_string(), r#"cpu-usage: "100" -> "80""#);
+        assert_eq!(expected[1].to_string(), r#"slot 00 machine-id: "0" -> "1""#);
+        let unknown = Change {
+            old: None,
+            ..expected[1].clone()
+        };
+        assert_eq!(unknown.to_string(), r#"slot 00 machine-id: "1""#);
+
+        // A dry run does not set anything
+        assert_eq!(desired.apply(&mut api, true).unwrap(), expected);
+        assert_eq!(server.state().options["cpu-usage"], "100");
+
+        assert_eq!(desired.apply(&mut api, false).unwrap(), expected);
+        assert_eq!(server.state().options["cpu-usage"], "80");
+        assert_eq!(server.state().slots[0].status, SlotStatus::Paused);
+        assert_eq!(server.state().slots[0].options["machine-id"], "1");
+        // Only the options that differ are set
+        let commands = server.state().commands.len();
+        assert_eq!(desired.apply(&mut api, false).unwrap(), vec![]);
+        assert!(server.state().commands[commands..]
+            .iter()
+            .all(|c| !c.contains("cpu-usage=")));
+
+        let typo = DesiredOptions::from_json(r#"{"cpu-usgae": 80}"#).unwrap();
+        assert!(typo.diff(&mut api).is_err());
+    }
+
+    #[test]
+    fn test_desired_options_rollback() {
+        let server = testing::MockServer::start().unwrap();
+        let mut api =
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+
+        // The mock client does not have the verbosity option, so setting it fails
+        let desired =
+            DesiredOptions::from_json(r#"{"cpu-usage": 50, "power": "FULL", "verbosity": 5}"#)
+                .unwrap();
+        assert!(desired.apply(&mut api, false).is_err());
+        assert_eq!(server.state().options["cpu-usage"], "100");
+        assert_eq!(server.state().options["power"], "MEDIUM");
+    }
+}
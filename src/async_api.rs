@@ -0,0 +1,267 @@
+use super::*;
+use std::net;
+
+/// Async version of [`API`](./struct.API.html). Enable the `async` feature to use this type.
+///
+/// Example
+/// ```no_run
+/// async fn example() -> fahapi::Result<()> {
+///     let mut api = fahapi::AsyncAPI::connect_timeout(&fahapi::DEFAULT_ADDR, std::time::Duration::from_secs(1)).await?;
+///     api.pause_all().await?;
+///     api.unpause_all().await
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsyncAPI {
+    pub conn: AsyncConnection,
+    pub buf: Vec<u8>,
+}
+
+impl AsyncAPI {
+    /// Connects to your FAH client with a timeout. `DEFAULT_ADDR` is the default address.
+    pub async fn connect_timeout(
+        addr: &net::SocketAddr,
+        timeout: core::time::Duration,
+    ) -> Result<AsyncAPI> {
+        Ok(AsyncAPI {
+            conn: AsyncConnection::connect_timeout(addr, timeout).await?,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Connects to a FAH client that requires a password. See
+    /// [`API::connect_with_password()`](./struct.API.html#method.connect_with_password).
+    pub async fn connect_with_password(
+        addr: &net::SocketAddr,
+        password: &str,
+        timeout: core::time::Duration,
+    ) -> Result<AsyncAPI> {
+        Ok(AsyncAPI {
+            conn: AsyncConnection::connect_with_password(addr, password, timeout).await?,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Returns a listing of the FAH API commands.
+    pub async fn help(&mut self) -> Result<String> {
+        self.conn.exec("help", &mut self.buf).await?;
+        Ok(std::str::from_utf8(&self.buf)?.to_string())
+    }
+
+    /// Enables or disables log updates. Returns current log. Use
+    /// [`AsyncLogStream`](./struct.AsyncLogStream.html) to receive the log updates that follow.
+    pub async fn log_updates(&mut self, arg: LogUpdatesArg) -> Result<String> {
+        /*
+            This command is weird. It returns the log after the next prompt, like this:
+            > log-updates start
+
+            >
+            PyON 1 log-update...
+        */
+
+        let command = format!("log-updates {}", arg);
+        self.conn.exec(command.as_str(), &mut self.buf).await?;
+        self.conn.exec_eval("eval", &mut self.buf).await?;
+
+        // The string contains a bunch of \x00 sequences that are not valid JSON and cannot be
+        // parsed using parse_pyon().
+        parse_log(std::str::from_utf8(&self.buf)?)
+    }
+
+    /// Unpauses all slots which are paused waiting for a screensaver and pause them again on
+    /// disconnect.
+    pub async fn screensaver(&mut self) -> Result<()> {
+        self.conn.exec("screensaver", &mut self.buf).await
+    }
+
+    /// Sets a slot to be always on.
+    pub async fn always_on(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("always_on {}", slot.0);
+        self.conn.exec(command.as_str(), &mut self.buf).await
+    }
+
+    /// Returns true if the client has set a user, team or passkey.
+    pub async fn configured(&mut self) -> Result<bool> {
+        self.conn.exec("configured", &mut self.buf).await?;
+        parse_pyon(&self.buf)
+    }
+
+    /// Runs one client cycle.
+    pub async fn do_cycle(&mut self) -> Result<()> {
+        self.conn.exec("do-cycle", &mut self.buf).await
+    }
+
+    /// Pauses a slot when its current work unit is completed.
+    pub async fn finish_slot(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("finish {}", slot.0);
+        self.conn.exec(command.as_str(), &mut self.buf).await
+    }
+
+    /// Pauses all slots one-by-one when their current work unit is completed.
+    pub async fn finish_all(&mut self) -> Result<()> {
+        self.conn.exec("finish", &mut self.buf).await
+    }
+
+    /// Returns FAH build and machine info. See `info_struct()`.
+    pub async fn info(&mut self) -> Result<Vec<Vec<serde_json::Value>>> {
+        self.conn.exec("info", &mut self.buf).await?;
+        parse_pyon(&self.buf)
+    }
+
+    /// Converts Info() data into a structure. Consider this interface to be very unstable.
+    pub async fn info_struct(&mut self) -> Result<Info> {
+        Info::new(self.info().await?)
+    }
+
+    /// Returns the number of slots.
+    pub async fn num_slots(&mut self) -> Result<i64> {
+        self.conn.exec("num-slots", &mut self.buf).await?;
+        parse_pyon(&self.buf)
+    }
+
+    /// Sets a slot to run only when idle.
+    pub async fn on_idle(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("on_idle {}", slot.0);
+        self.conn.exec(command.as_str(), &mut self.buf).await
+    }
+
+    /// Sets all slots to run only when idle.
+    pub async fn on_idle_all(&mut self) -> Result<()> {
+        self.conn.exec("on_idle", &mut self.buf).await
+    }
+
+    /// Returns the FAH client options.
+    pub async fn options_get(&mut self) -> Result<Options> {
+        self.conn.exec("options -a", &mut self.buf).await?;
+        parse_pyon(&self.buf)
+    }
+
+    /// Sets an option. The value is quoted if it contains spaces, quotes or other special
+    /// characters.
+    pub async fn options_set<N>(&mut self, key: &str, value: N) -> Result<()>
+    where
+        N: std::fmt::Display,
+    {
+        let command = options_set_command(key, value)?;
+        self.conn.exec(command.as_str(), &mut self.buf).await
+    }
+
+    /// Sets an option after checking that its value is valid.
+    pub async fn set_option(&mut self, option: OptionKey) -> Result<()> {
+        let command = set_option_command(&option)?;
+        self.conn.exec(command.as_str(), &mut self.buf).await
+    }
+
+    /// Pauses all slots.
+    pub async fn pause_all(&mut self) -> Result<()> {
+        self.conn.exec("pause", &mut self.buf).await
+    }
+
+    /// Pauses a slot.
+    pub async fn pause_slot(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("pause {}", slot.0);
+        self.conn.exec(command.as_str(), &mut self.buf).await
+    }
+
+    // Returns the total estimated points per day.
+    pub async fn ppd(&mut self) -> Result<f64> {
+        self.conn.exec("ppd", &mut self.buf).await?;
+        parse_pyon(&self.buf)
+    }
+
+    /// Returns info about the current work unit.
+    pub async fn queue_info(&mut self) -> Result<Vec<SlotQueueInfo>> {
+        self.conn.exec("queue-info", &mut self.buf).await?;
+        parse_pyon(&self.buf)
+    }
+
+    /// Requests an ID from the assignment server.
+    pub async fn request_id(&mut self) -> Result<()> {
+        self.conn.exec("request-id", &mut self.buf).await
+    }
+
+    /// Requests work server assignment from the assignment server.
+    pub async fn request_ws(&mut self) -> Result<()> {
+        self.conn.exec("request-ws", &mut self.buf).await
+    }
+
+    /// Ends all FAH processes.
+    pub async fn shutdown(&mut self) -> Result<()> {
+        self.conn.exec("shutdown", &mut self.buf).await
+    }
+
+    /// Returns the simulation information for a slot.
+    pub async fn simulation_info(&mut self, slot: SlotId) -> Result<SimulationInfo> {
+        // "just like the simulations"
+        let command = format!("simulation-info {}", slot.0);
+        self.conn.exec(command.as_str(), &mut self.buf).await?;
+        parse_pyon(&self.buf)
+    }
+
+    /// Adds a slot. The slot gets the next unused ID and is returned by `slot_info()`.
+    pub async fn slot_add(&mut self, slot_type: SlotType) -> Result<()> {
+        let command = format!("slot-add {}", slot_args(&slot_type, &[])?);
+        self.conn.exec(command.as_str(), &mut self.buf).await
+    }
+
+    /// Changes the type of a slot and sets slot options. The value is quoted if necessary.
+    pub async fn slot_modify(
+        &mut self,
+        slot: SlotId,
+        slot_type: SlotType,
+        options: &[(&str, &str)],
+    ) -> Result<()> {
+        let command = format!("slot-modify {} {}", slot.0, slot_args(&slot_type, options)?);
+        self.conn.exec(command.as_str(), &mut self.buf).await
+    }
+
+    /// Deletes a slot.
+    pub async fn slot_delete(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("slot-delete {}", slot.0);
+        self.conn.exec(command.as_str(), &mut self.buf).await
+    }
+
+    /// Returns information about each slot.
+    pub async fn slot_info(&mut self) -> Result<Vec<SlotInfo>> {
+        self.conn.exec("slot-info", &mut self.buf).await?;
+        parse_pyon(&self.buf)
+    }
+
+    /// Returns slot options.
+    pub async fn slot_options_get(&mut self, slot: SlotId) -> Result<SlotOptions> {
+        let command = format!("slot-options {} -a", slot.0);
+        self.conn.exec(command.as_str(), &mut self.buf).await?;
+        parse_pyon(&self.buf)
+    }
+
+    /// Sets slot option. The value is quoted if necessary.
+    pub async fn slot_options_set<N>(&mut self, slot: SlotId, key: &str, value: N) -> Result<()>
+    where
+        N: std::fmt::Display,
+    {
+        let command = slot_options_set_command(slot, key, value)?;
+        self.conn.exec(command.as_str(), &mut self.buf).await
+    }
+
+    /// Unpauses all slots.
+    pub async fn unpause_all(&mut self) -> Result<()> {
+        self.conn.exec("unpause", &mut self.buf).await
+    }
+
+    /// Unpauses a slot.
+    pub async fn unpause_slot(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("unpause {}", slot.0);
+        self.conn.exec(command.as_str(), &mut self.buf).await
+    }
+
+    /// Returns FAH uptime.
+    pub async fn uptime(&mut self) -> Result<FAHDuration> {
+        self.conn.exec_eval("uptime", &mut self.buf).await?;
+        parse_uptime(&self.buf)
+    }
+
+    /// Blocks until all slots are paused.
+    pub async fn wait_for_units(&mut self) -> Result<()> {
+        self.conn.exec("wait-for-units", &mut self.buf).await
+    }
+}
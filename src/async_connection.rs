@@ -0,0 +1,229 @@
+use super::*;
+use std::net;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Async version of [`Connection`](./struct.Connection.html).
+#[derive(Debug)]
+pub struct AsyncConnection {
+    pub conn: tokio::net::TcpStream,
+    pub addr: net::SocketAddr,
+    pub connect_timeout: core::time::Duration,
+    /// Password that is sent with the `auth` command after connecting.
+    pub password: Option<String>,
+}
+
+impl AsyncConnection {
+    pub async fn connect_timeout(
+        addr: &net::SocketAddr,
+        timeout: core::time::Duration,
+    ) -> Result<Self> {
+        Ok(Self {
+            conn: connect_timeout_async(addr, timeout, None).await?,
+            addr: *addr,
+            connect_timeout: timeout,
+            password: None,
+        })
+    }
+
+    /// Connects and authenticates with the password. The password is sent again when
+    /// reconnecting.
+    pub async fn connect_with_password(
+        addr: &net::SocketAddr,
+        password: &str,
+        timeout: core::time::Duration,
+    ) -> Result<Self> {
+        Ok(Self {
+            conn: connect_timeout_async(addr, timeout, Some(password)).await?,
+            addr: *addr,
+            connect_timeout: timeout,
+            password: Some(password.to_string()),
+        })
+    }
+
+    /// Executes a command on the FAH client. The response is written to the buffer.
+    pub async fn exec(&mut self, command: &str, buf: &mut Vec<u8>) -> Result<()> {
+        if command.is_empty() {
+            // FAH doesn't respond to an empty command
+            buf.clear();
+            return Ok(());
+        }
+
+        if command.contains('\n') {
+            return Err(Error::InvalidCommand(
+                "command contains newline".to_string(),
+            ));
+        }
+
+        self.conn
+            .write_all(format!("{}\n", command).as_bytes())
+            .await?;
+
+        if let Err(e) = read_message_async(&mut self.conn, buf).await {
+            // Try to reconnect on disconnection
+            if let Error::Disconnected = e {
+                self.reconnect().await?;
+            }
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the TCP connection with a new one. The password is sent if it is set.
+    pub async fn reconnect(&mut self) -> Result<()> {
+        self.conn =
+            connect_timeout_async(&self.addr, self.connect_timeout, self.password.as_deref())
+                .await?;
+        Ok(())
+    }
+
+    /// Executes commands which do not return a trailing newline. The response is written to the
+    /// buffer.
+    pub async fn exec_eval(&mut self, command: &str, buf: &mut Vec<u8>) -> Result<()> {
+        if command.is_empty() {
+            // FAH doesn't respond to an empty command
+            buf.clear();
+            return Ok(());
+        }
+
+        self.exec(eval_command(command).as_str(), buf).await?;
+        trim_eval_response(buf);
+        Ok(())
+    }
+}
+
+/// Connects, reads the welcome message and authenticates, all within the timeout.
+async fn connect_timeout_async(
+    addr: &net::SocketAddr,
+    timeout: core::time::Duration,
+    password: Option<&str>,
+) -> Result<tokio::net::TcpStream> {
+    tokio::time::timeout(timeout, connect_async(addr, password))
+        .await
+        .map_err(|_| Error::Timeout)?
+}
+
+async fn connect_async(
+    addr: &net::SocketAddr,
+    password: Option<&str>,
+) -> Result<tokio::net::TcpStream> {
+    let mut conn = tokio::net::TcpStream::connect(addr).await?;
+
+    // Discard welcome message
+    let mut buf: Vec<u8> = Vec::new();
+    read_message_async(&mut conn, &mut buf).await?;
+
+    if let Some(password) = password {
+        conn.write_all(auth_command(password)?.as_bytes()).await?;
+        read_message_async(&mut conn, &mut buf).await?;
+        check_auth_response(&buf)?;
+    }
+
+    Ok(conn)
+}
+
+/// Async version of [`read_message()`](./fn.read_message.html).
+pub async fn read_message_async(
+    r: &mut (impl tokio::io::AsyncRead + Unpin),
+    buf: &mut Vec<u8>,
+) -> Result<()> {
+    buf.clear();
+    loop {
+        let mut b: [u8; 1] = [0];
+        if r.read(&mut b).await? == 0 {
+            return Err(Error::Disconnected);
+        }
+
+        buf.push(b[0]);
+
+        if trim_end_of_message(buf) {
+            return Ok(());
+        }
+    }
+}
+
+/// Async version of [`read_pyon_message()`](./fn.read_pyon_message.html).
+pub async fn read_pyon_message_async(
+    r: &mut (impl tokio::io::AsyncRead + Unpin),
+    buf: &mut Vec<u8>,
+) -> Result<()> {
+    buf.clear();
+    loop {
+        let mut b: [u8; 1] = [0];
+        if r.read(&mut b).await? == 0 {
+            return Err(Error::Disconnected);
+        }
+
+        buf.push(b[0]);
+
+        if trim_pyon_message(buf) {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_message_async() {
+        struct Test {
+            s: &'static [u8],
+            expected: &'static [u8],
+            expect_error: bool,
+        }
+
+        let tests = [
+            Test {
+                s: b"",
+                expected: b"",
+                expect_error: true,
+            },
+            Test {
+                s: b"\n",
+                expected: b"\n",
+                expect_error: true,
+            },
+            Test {
+                s: b"a\n> ",
+                expected: b"a",
+                expect_error: false,
+            },
+            Test {
+                s: b"\na\n> ",
+                expected: b"a",
+                expect_error: false,
+            },
+        ];
+
+        let mut buf: Vec<u8> = Vec::new();
+        for (i, test) in tests.iter().enumerate() {
+            let result = read_message_async(&mut &test.s[..], &mut buf).await;
+            assert_eq!(result.is_err(), test.expect_error);
+            assert_eq!(buf.as_slice(), test.expected, "{}", i);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_timeout_async() {
+        // Accepts connections but never sends the welcome message
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut streams = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                streams.push(stream);
+            }
+        });
+
+        let timeout = core::time::Duration::from_millis(200);
+        let start = std::time::Instant::now();
+        let result = AsyncConnection::connect_timeout(&addr, timeout).await;
+        assert!(matches!(result, Err(Error::Timeout)));
+        let result = AsyncConnection::connect_with_password(&addr, "secret", timeout).await;
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert!(start.elapsed() < core::time::Duration::from_secs(2));
+    }
+}
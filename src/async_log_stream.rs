@@ -0,0 +1,151 @@
+use super::*;
+use std::net;
+use tokio::io::AsyncWriteExt;
+
+/// Async version of [`LogStream`](./struct.LogStream.html). Use
+/// [`into_stream()`](#method.into_stream) to get a `futures::Stream`.
+///
+/// Example
+/// ```no_run
+/// async fn example() -> fahapi::Result<()> {
+///     let mut stream = fahapi::AsyncLogStream::connect_timeout(
+///         &fahapi::DEFAULT_ADDR,
+///         std::time::Duration::from_secs(1),
+///     ).await?;
+///     while let Some(chunk) = stream.next().await {
+///         print!("{}", chunk?);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsyncLogStream {
+    pub conn: AsyncConnection,
+    buf: Vec<u8>,
+    received: usize,
+    stopped: bool,
+}
+
+impl AsyncLogStream {
+    /// Connects to your FAH client with a timeout and starts log updates.
+    pub async fn connect_timeout(
+        addr: &net::SocketAddr,
+        timeout: core::time::Duration,
+    ) -> Result<Self> {
+        Self::from_connection(AsyncConnection::connect_timeout(addr, timeout).await?).await
+    }
+
+    /// Connects to a FAH client that requires a password and starts log updates.
+    pub async fn connect_with_password(
+        addr: &net::SocketAddr,
+        password: &str,
+        timeout: core::time::Duration,
+    ) -> Result<Self> {
+        Self::from_connection(
+            AsyncConnection::connect_with_password(addr, password, timeout).await?,
+        )
+        .await
+    }
+
+    /// Starts log updates on the connection. The connection should not be used for other
+    /// commands.
+    pub async fn from_connection(mut conn: AsyncConnection) -> Result<Self> {
+        conn.conn
+            .write_all(format!("log-updates {}\n", LogUpdatesArg::Start).as_bytes())
+            .await?;
+        Ok(Self {
+            conn,
+            buf: Vec::new(),
+            received: 0,
+            stopped: false,
+        })
+    }
+
+    /// Returns the next log text. See [`LogStream`](./struct.LogStream.html).
+    pub async fn next(&mut self) -> Option<Result<String>> {
+        while !self.stopped {
+            if let Err(e) = read_pyon_message_async(&mut self.conn.conn, &mut self.buf).await {
+                if let Error::Disconnected = e {
+                    match self.restart().await {
+                        Ok(()) => continue,
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+                return Some(Err(e));
+            }
+
+            match log_chunk(&self.buf, &mut self.received) {
+                Ok(Some(chunk)) => return Some(Ok(chunk)),
+                Ok(None) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+
+    /// Converts this into a `futures::Stream` of log text.
+    pub fn into_stream(self) -> impl futures_util::Stream<Item = Result<String>> {
+        futures_util::stream::unfold(self, |mut stream| async move {
+            stream.next().await.map(|item| (item, stream))
+        })
+    }
+
+    /// Sends `log-updates stop` and waits for the client to acknowledge it. `next()` returns
+    /// `None` after this.
+    pub async fn stop(&mut self) -> Result<()> {
+        self.stopped = true;
+        self.conn
+            .conn
+            .write_all(format!("log-updates {}\n", LogUpdatesArg::Stop).as_bytes())
+            .await?;
+        read_message_async(&mut self.conn.conn, &mut self.buf).await
+    }
+
+    async fn restart(&mut self) -> Result<()> {
+        self.conn.reconnect().await?;
+        self.conn
+            .conn
+            .write_all(format!("log-updates {}\n", LogUpdatesArg::Restart).as_bytes())
+            .await?;
+        Ok(())
+    }
+}
+
+impl Drop for AsyncLogStream {
+    fn drop(&mut self) {
+        if !self.stopped {
+            // Cannot wait in drop, so the command is only sent if it can be written immediately
+            let _ = self
+                .conn
+                .conn
+                .try_write(format!("log-updates {}\n", LogUpdatesArg::Stop).as_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[tokio::test]
+    async fn test_async_log_stream() {
+        use futures_util::StreamExt;
+
+        let server = testing::MockServer::start().unwrap();
+        server.state().log = "a\n".to_string();
+
+        let mut stream =
+            AsyncLogStream::connect_timeout(&server.addr(), core::time::Duration::from_secs(1))
+                .await
+                .unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), "a\n");
+        server.disconnect();
+        server.append_log("b\n").unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), "b\n");
+
+        let mut stream = Box::pin(stream.into_stream());
+        server.append_log("c\n").unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), "c\n");
+    }
+}
@@ -0,0 +1,495 @@
+//! Command line interface for the FAH client. Build with `cargo build --features cli`.
+
+use clap::{Parser, Subcommand};
+use fahapi::*;
+use serde_json::json;
+use std::io::Write;
+
+const TIMEOUT: core::time::Duration = core::time::Duration::from_secs(5);
+
+/// Controls a Folding@home client through its command port.
+#[derive(Parser, Debug)]
+#[command(name = "fahctl", version)]
+struct Args {
+    /// Host of the FAH client
+    #[arg(long, default_value = "127.0.0.1", global = true)]
+    host: String,
+    /// Command port of the FAH client
+    #[arg(long, default_value_t = 36330, global = true)]
+    port: u16,
+    /// Password of the command port
+    #[arg(long, global = true)]
+    password: Option<String>,
+    /// Print JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Shows each slot with its work units and the total PPD
+    Status,
+    /// Pauses a slot, or all slots
+    Pause { slot: Option<SlotId> },
+    /// Unpauses a slot, or all slots
+    Unpause { slot: Option<SlotId> },
+    /// Pauses a slot, or all slots, after the current work unit is finished
+    Finish { slot: Option<SlotId> },
+    /// Gets or sets client options
+    #[command(subcommand)]
+    Options(OptionsCommand),
+    /// Gets the options of a slot, or sets one if a key and value are given
+    SlotOptions {
+        slot: SlotId,
+        key: Option<String>,
+        value: Option<String>,
+    },
+    /// Sets the options in a TOML or JSON file that differ from the client's options
+    Apply {
+        /// TOML if the name ends with .toml, otherwise JSON
+        file: std::path::PathBuf,
+        /// Print the changes without setting them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Saves all client and slot options to a file
+    Backup { file: std::path::PathBuf },
+    /// Sets the options that differ from a file saved by backup
+    Restore { file: std::path::PathBuf },
+    /// Shows the work queue
+    Queue,
+    /// Shows the estimated points per day
+    Ppd,
+    /// Shows how long the client has been running
+    Uptime,
+    /// Prints the log
+    Log {
+        /// Keep printing the log as it is written
+        #[arg(long, short)]
+        follow: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum OptionsCommand {
+    /// Prints all options, or one option
+    Get { key: Option<String> },
+    /// Sets an option
+    Set { key: String, value: String },
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(e) = run(&args, &mut std::io::stdout().lock()) {
+        eprintln!("fahctl: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &Args, out: &mut impl Write) -> Result<()> {
+    use std::net::ToSocketAddrs;
+
+    let addr = (args.host.as_str(), args.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| Error::InvalidCommand(format!("cannot resolve {}", args.host)))?;
+
+    if let Command::Log { follow: true } = args.command {
+        let stream = match &args.password {
+            Some(password) => LogStream::connect_with_password(&addr, password, TIMEOUT)?,
+            None => LogStream::connect_timeout(&addr, TIMEOUT)?,
+        };
+        let mut parser = LogParser::new();
+        for chunk in stream {
+            write_log(out, &chunk?, &mut parser, args.json)?;
+            out.flush()?;
+        }
+        return Ok(());
+    }
+
+    let mut api = match &args.password {
+        Some(password) => API::connect_with_password(&addr, password, TIMEOUT)?,
+        None => API::connect_timeout(&addr, TIMEOUT)?,
+    };
+
+    match &args.command {
+        Command::Status => {
+            let slots = api.slot_info()?;
+            let units = api.queue_info()?;
+            let ppd = api.ppd()?;
+            if args.json {
+                let slots = slots
+                    .iter()
+                    .map(|slot| {
+                        let mut value = to_json(slot)?;
+                        let slot_units: Vec<&SlotQueueInfo> =
+                            units.iter().filter(|unit| unit.slot == slot.id).collect();
+                        value["units"] = to_json(&slot_units)?;
+                        Ok(value)
+                    })
+                    .collect::<Result<Vec<serde_json::Value>>>()?;
+                write_json(out, &json!({"ppd": ppd, "slots": slots}))
+            } else {
+                write!(out, "{}", status_table(&slots, &units))?;
+                writeln!(out, "\nPPD: {}", ppd.round())?;
+                Ok(())
+            }
+        }
+        Command::Pause { slot } => match slot {
+            Some(slot) => api.pause_slot(*slot),
+            None => api.pause_all(),
+        },
+        Command::Unpause { slot } => match slot {
+            Some(slot) => api.unpause_slot(*slot),
+            None => api.unpause_all(),
+        },
+        Command::Finish { slot } => match slot {
+            Some(slot) => api.finish_slot(*slot),
+            None => api.finish_all(),
+        },
+        Command::Options(OptionsCommand::Get { key }) => {
+            let mut options = to_json(&api.options_get()?)?;
+            if let Some(key) = key {
+                if options.get(key).is_none() {
+                    // Options does not have a field for every option of the client
+                    options = exec_pyon(&mut api, "options -a")?;
+                }
+            }
+            write_options(out, options, key.as_deref(), args.json)
+        }
+        Command::Options(OptionsCommand::Set { key, value }) => api.options_set(key, value),
+        Command::SlotOptions {
+            slot,
+            key: Some(key),
+            value: Some(value),
+        } => api.slot_options_set(*slot, key, value),
+        Command::SlotOptions { slot, key, .. } => {
+            // Options without a field are in SlotOptions::extra
+            let options = to_json(&api.slot_options_get(*slot)?)?;
+            write_options(out, options, key.as_deref(), args.json)
+        }
+        Command::Apply { file, dry_run } => {
+            let s = std::fs::read_to_string(file)?;
+            let desired = if file.extension().is_some_and(|ext| ext == "toml") {
+                DesiredOptions::from_toml(&s)?
+            } else {
+                DesiredOptions::from_json(&s)?
+            };
+            let changes = desired.apply(&mut api, *dry_run)?;
+            if args.json {
+                let changes = changes
+                    .iter()
+                    .map(|change| {
+                        json!({
+                            "slot": change.slot.map(|slot| slot.to_string()),
+                            "key": change.key,
+                            "old": change.old,
+                            "new": change.new,
+                        })
+                    })
+                    .collect();
+                write_json(out, &changes)
+            } else {
+                for change in changes {
+                    writeln!(out, "{}", change)?;
+                }
+                Ok(())
+            }
+        }
+        Command::Backup { file } => {
+            let host = format!("{}:{}", args.host, args.port);
+            Snapshot::take(&mut api, &host)?.save(file)
+        }
+        Command::Restore { file } => {
+            for warning in Snapshot::load(file)?.restore(&mut api)? {
+                eprintln!("fahctl: warning: {}", warning);
+            }
+            Ok(())
+        }
+        Command::Queue => {
+            let units = api.queue_info()?;
+            if args.json {
+                write_json(out, &to_json(&units)?)
+            } else {
+                write!(out, "{}", queue_table(&units))?;
+                Ok(())
+            }
+        }
+        Command::Ppd => {
+            let ppd = api.ppd()?;
+            if args.json {
+                write_json(out, &json!(ppd))
+            } else {
+                writeln!(out, "{}", ppd.round())?;
+                Ok(())
+            }
+        }
+        Command::Uptime => {
+            let uptime = api.uptime()?;
+            if args.json {
+                write_json(out, &json!(uptime.0.map(|d| d.num_seconds())))
+            } else {
+                writeln!(out, "{}", format_duration(uptime))?;
+                Ok(())
+            }
+        }
+        Command::Log { .. } => {
+            let log = api.log_updates(LogUpdatesArg::Start)?;
+            write_log(out, &log, &mut LogParser::new(), args.json)
+        }
+    }
+}
+
+/// Executes a command that returns a PyON message and returns the message as JSON.
+fn exec_pyon(api: &mut API, command: &str) -> Result<serde_json::Value> {
+    api.conn.exec(command, &mut api.buf)?;
+    pyon::parse_message(std::str::from_utf8(&api.buf)?)
+}
+
+fn to_json(value: &impl serde::Serialize) -> Result<serde_json::Value> {
+    serde_json::to_value(value).map_err(|e| Error::Other(e.into()))
+}
+
+fn write_json(out: &mut impl Write, value: &serde_json::Value) -> Result<()> {
+    writeln!(out, "{}", serde_json::to_string_pretty(value).unwrap())?;
+    Ok(())
+}
+
+/// Writes an options dict, or only the option with the key.
+fn write_options(
+    out: &mut impl Write,
+    options: serde_json::Value,
+    key: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    let mut options = match options {
+        serde_json::Value::Object(options) => options,
+        _ => return Err(Error::UnexpectedShape("options is not a dict".to_string())),
+    };
+    if let Some(key) = key {
+        let value = options
+            .remove(key)
+            .ok_or_else(|| Error::InvalidCommand(format!("unknown option: {}", key)))?;
+        options = serde_json::Map::new();
+        options.insert(key.to_string(), value);
+    }
+
+    if json {
+        return write_json(out, &serde_json::Value::Object(options));
+    }
+
+    let rows = options
+        .iter()
+        .map(|(k, v)| {
+            let v = match v {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Null => String::new(),
+                v => v.to_string(),
+            };
+            vec![k.clone(), v]
+        })
+        .collect();
+    write!(out, "{}", table(&["OPTION", "VALUE"], rows))?;
+    Ok(())
+}
+
+/// Writes log text, or each log line as JSON.
+fn write_log(out: &mut impl Write, log: &str, parser: &mut LogParser, json: bool) -> Result<()> {
+    if !json {
+        write!(out, "{}", log)?;
+        return Ok(());
+    }
+
+    for line in parser.parse(log) {
+        let level = match line.level {
+            LogLevel::Info => "info",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+        };
+        let value = json!({
+            "time": line.time.to_string(),
+            "timestamp": line.timestamp.map(|t| t.to_rfc3339()),
+            "level": level,
+            "queue-id": line.queue_id.map(|id| id.0),
+            "slot": line.slot.map(|id| id.0),
+            "core": line.core,
+            "message": line.message,
+        });
+        writeln!(out, "{}", value)?;
+    }
+    Ok(())
+}
+
+fn status_table(slots: &[SlotInfo], units: &[SlotQueueInfo]) -> String {
+    let mut rows = Vec::new();
+    for slot in slots {
+        let slot_units: Vec<&SlotQueueInfo> = units.iter().filter(|u| u.slot == slot.id).collect();
+        let mut row = vec![
+            slot.id.to_string(),
+            slot.status.to_string(),
+            slot.description.clone(),
+        ];
+        match slot_units.first() {
+            Some(unit) => row.extend(vec![
+                unit.prcg().to_string(),
+                unit.percent_done.to_string(),
+                format_duration(unit.eta),
+                unit.ppd.0.to_string(),
+            ]),
+            None => row.extend(vec![String::new(); 4]),
+        }
+        rows.push(row);
+    }
+    table(
+        &[
+            "SLOT",
+            "STATUS",
+            "DESCRIPTION",
+            "WORK UNIT",
+            "PROGRESS",
+            "ETA",
+            "PPD",
+        ],
+        rows,
+    )
+}
+
+fn queue_table(units: &[SlotQueueInfo]) -> String {
+    let rows = units
+        .iter()
+        .map(|unit| {
+            vec![
+                unit.id.to_string(),
+                unit.slot.to_string(),
+                unit.state.to_string(),
+                unit.prcg().to_string(),
+                unit.percent_done.to_string(),
+                format_duration(unit.eta),
+                unit.credit_estimate.0.to_string(),
+                unit.deadline.to_string(),
+            ]
+        })
+        .collect();
+    table(
+        &[
+            "ID",
+            "SLOT",
+            "STATE",
+            "WORK UNIT",
+            "PROGRESS",
+            "ETA",
+            "CREDIT",
+            "DEADLINE",
+        ],
+        rows,
+    )
+}
+
+/// Formats rows into left-aligned columns.
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let headers = headers.iter().map(|h| h.to_string()).collect();
+    let mut result = String::new();
+    for row in std::iter::once(headers).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        result.push_str(cells.join("  ").trim_end());
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_table() {
+        let rows = vec![
+            vec!["00".to_string(), "RUNNING".to_string(), "cpu:4".to_string()],
+            vec!["01".to_string(), "PAUSED".to_string(), String::new()],
+        ];
+        assert_eq!(
+            table(&["SLOT", "STATUS", "DESCRIPTION"], rows),
+            "SLOT  STATUS   DESCRIPTION\n00    RUNNING  cpu:4\n01    PAUSED\n"
+        );
+        assert_eq!(table(&["A"], Vec::new()), "A\n");
+    }
+
+    #[test]
+    fn test_status_table() {
+        let slots = vec![SlotInfo {
+            id: SlotId(0),
+            status: SlotStatus::Running,
+            description: "cpu:4".to_string(),
+            ..Default::default()
+        }];
+        let units = vec![SlotQueueInfo {
+            slot: SlotId(0),
+            project: 13424,
+            run: 84,
+            clone: 7,
+            gen: 37,
+            percent_done: Percent(42.17),
+            eta: chrono::Duration::seconds(8000).into(),
+            ppd: 248734.into(),
+            ..Default::default()
+        }];
+        assert_eq!(
+            status_table(&slots, &units),
+            "SLOT  STATUS   DESCRIPTION  WORK UNIT          PROGRESS  ETA     PPD\n\
+             00    RUNNING  cpu:4        P13424 R84 C7 G37  42.17%    2h 13m  248734\n"
+        );
+    }
+
+    #[test]
+    fn test_write_options() {
+        let options = json!({"power": "MEDIUM", "passkey": null});
+
+        let mut out = Vec::new();
+        write_options(&mut out, options.clone(), None, false).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "OPTION   VALUE\npasskey\npower    MEDIUM\n"
+        );
+
+        let mut out = Vec::new();
+        write_options(&mut out, options.clone(), Some("power"), true).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\n  \"power\": \"MEDIUM\"\n}\n"
+        );
+
+        assert!(write_options(&mut Vec::new(), options, Some("a"), false).is_err());
+    }
+
+    #[test]
+    fn test_args() {
+        let args = Args::try_parse_from(["fahctl", "--port", "1", "pause", "2", "--json"]).unwrap();
+        assert_eq!(args.port, 1);
+        assert!(args.json);
+        assert!(matches!(
+            args.command,
+            Command::Pause {
+                slot: Some(SlotId(2))
+            }
+        ));
+
+        let args = Args::try_parse_from(["fahctl", "log", "-f"]).unwrap();
+        assert!(matches!(args.command, Command::Log { follow: true }));
+
+        assert!(Args::try_parse_from(["fahctl", "options", "set", "power"]).is_err());
+    }
+}
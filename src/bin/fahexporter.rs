@@ -0,0 +1,46 @@
+//! Prometheus exporter for the FAH client. Build with `cargo build --features metrics`.
+
+use clap::Parser;
+use fahapi::metrics::Exporter;
+use fahapi::*;
+
+const TIMEOUT: core::time::Duration = core::time::Duration::from_secs(5);
+
+/// Serves metrics of a Folding@home client at /metrics for Prometheus.
+#[derive(Parser, Debug)]
+#[command(name = "fahexporter", version)]
+struct Args {
+    /// Host of the FAH client
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+    /// Command port of the FAH client
+    #[arg(long, default_value_t = 36330)]
+    port: u16,
+    /// Password of the command port
+    #[arg(long)]
+    password: Option<String>,
+    /// Address to serve metrics on
+    #[arg(long, default_value = "0.0.0.0:9659")]
+    listen: String,
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(e) = run(&args) {
+        eprintln!("fahexporter: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &Args) -> Result<()> {
+    use std::net::ToSocketAddrs;
+
+    let addr = (args.host.as_str(), args.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| Error::InvalidCommand(format!("cannot resolve {}", args.host)))?;
+
+    let listener = std::net::TcpListener::bind(&args.listen)?;
+    // The client may start after the exporter, so it is not required to be up
+    Exporter::connect(&addr, args.password.as_deref(), TIMEOUT).serve(&listener)
+}
@@ -0,0 +1,108 @@
+//! Terminal dashboard for the FAH client. Build with `cargo build --features tui`.
+
+use clap::Parser;
+use fahapi::tui::App;
+use fahapi::*;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use std::sync::mpsc;
+
+const TIMEOUT: core::time::Duration = core::time::Duration::from_secs(5);
+
+/// Shows the slots, work units and log of a Folding@home client.
+#[derive(Parser, Debug)]
+#[command(name = "fahtop", version)]
+struct Args {
+    /// Host of the FAH client
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+    /// Command port of the FAH client
+    #[arg(long, default_value_t = 36330)]
+    port: u16,
+    /// Password of the command port
+    #[arg(long)]
+    password: Option<String>,
+    /// Seconds between refreshes
+    #[arg(long, default_value_t = 2)]
+    interval: u64,
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(e) = run(&args) {
+        eprintln!("fahtop: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &Args) -> Result<()> {
+    use std::net::ToSocketAddrs;
+
+    let addr = (args.host.as_str(), args.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| Error::InvalidCommand(format!("cannot resolve {}", args.host)))?;
+
+    let mut api = match &args.password {
+        Some(password) => API::connect_with_password(&addr, password, TIMEOUT)?,
+        None => API::connect_timeout(&addr, TIMEOUT)?,
+    };
+    let stream = match &args.password {
+        Some(password) => LogStream::connect_with_password(&addr, password, TIMEOUT)?,
+        None => LogStream::connect_timeout(&addr, TIMEOUT)?,
+    };
+
+    // LogStream blocks, so it is read on another thread
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for chunk in stream {
+            if sender.send(chunk).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, &mut api, &receiver, args.interval);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    api: &mut API,
+    receiver: &mpsc::Receiver<Result<String>>,
+    interval: u64,
+) -> Result<()> {
+    let interval = core::time::Duration::from_secs(interval.max(1));
+    let mut app = App::new();
+    let mut last_refresh: Option<std::time::Instant> = None;
+
+    while !app.quit {
+        let refresh = match last_refresh {
+            Some(t) => t.elapsed() >= interval,
+            None => true,
+        };
+        if refresh {
+            app.refresh(api);
+            last_refresh = Some(std::time::Instant::now());
+        }
+
+        for chunk in receiver.try_iter() {
+            match chunk {
+                Ok(text) => app.append_log(&text),
+                Err(e) => app.error = Some(e.to_string()),
+            }
+        }
+
+        terminal.draw(|frame| app.render(frame))?;
+
+        if event::poll(core::time::Duration::from_millis(250))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle_key(key.code, api);
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,536 @@
+//! Reads and writes the FAH client's `config.xml`, for setting options while the client is not
+//! running. Enable the `config` feature to use this module.
+//!
+//! Options are elements like `<power v='full'/>`, and slots are elements like
+//! `<slot id='0' type='CPU'>` with their options as children. Comments, whitespace and elements
+//! that are not options are kept when the file is written.
+//!
+//! Example
+//! ```no_run
+//! let mut config = fahapi::config::Config::load("/etc/fahclient/config.xml").unwrap();
+//! config.set_option(fahapi::OptionKey::Power(fahapi::Power::PowerFull)).unwrap();
+//! let gpu = fahapi::SlotType::Gpu {
+//!     gpu_index: Some(0),
+//!     cuda_index: None,
+//!     opencl_index: None,
+//! };
+//! config.add_slot(fahapi::SlotId(1), &gpu).unwrap();
+//! config.save("/etc/fahclient/config.xml").unwrap();
+//! ```
+
+use super::*;
+use quick_xml::events::Event;
+use std::collections::BTreeMap;
+
+const ROOT: &str = "config";
+const SLOT: &str = "slot";
+const INDENT: &str = "  ";
+
+/// A parsed `config.xml`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Config {
+    /// Nodes before and after the root element, such as the XML declaration
+    prolog: Vec<Node>,
+    root: Element,
+    epilog: Vec<Node>,
+}
+
+/// A slot in `config.xml`.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ConfigSlot {
+    pub id: SlotId,
+    /// "CPU" or "GPU"
+    pub slot_type: String,
+    /// Slot options by their kebab-case names
+    pub options: BTreeMap<String, String>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Node {
+    Element(Element),
+    /// Escaped text, which is usually whitespace
+    Text(String),
+    Comment(String),
+    /// Markup that is written back as is, such as the XML declaration
+    Raw(String),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+struct Element {
+    name: String,
+    /// Unescaped attribute values
+    attributes: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl Element {
+    fn new(name: &str, attributes: &[(&str, &str)]) -> Self {
+        Self {
+            name: name.to_string(),
+            attributes: attributes
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            children: Vec::new(),
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn set_attribute(&mut self, name: &str, value: &str) {
+        match self.attributes.iter_mut().find(|(k, _)| k == name) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.attributes.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    fn elements_mut(&mut self) -> impl Iterator<Item = &mut Element> {
+        self.children.iter_mut().filter_map(|node| match node {
+            Node::Element(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    /// Returns option elements like `<power v='full'/>` as (name, value).
+    fn options(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.elements()
+            .filter(|e| e.name != SLOT)
+            .filter_map(|e| Some((e.name.as_str(), e.attribute("v")?)))
+    }
+
+    fn set_option(&mut self, key: &str, value: &str, depth: usize) {
+        let existing = self
+            .elements_mut()
+            .find(|e| e.name == key && e.attribute("v").is_some());
+        match existing {
+            Some(e) => e.set_attribute("v", value),
+            None => self.push(Element::new(key, &[("v", value)]), depth),
+        }
+    }
+
+    /// Appends a child on its own line, indented like a child at `depth + 1`.
+    fn push(&mut self, child: Element, depth: usize) {
+        let closing = match self.children.last() {
+            Some(Node::Text(text)) if text.trim().is_empty() => self.children.pop(),
+            _ => None,
+        };
+        self.children
+            .push(Node::Text(format!("\n{}", INDENT.repeat(depth + 1))));
+        self.children.push(Node::Element(child));
+        self.children
+            .push(closing.unwrap_or_else(|| Node::Text(format!("\n{}", INDENT.repeat(depth)))));
+    }
+
+    /// Removes the first child element for which `f` returns true, and the whitespace before it.
+    fn remove(&mut self, f: impl Fn(&Element) -> bool) -> bool {
+        let i = match self
+            .children
+            .iter()
+            .position(|node| matches!(node, Node::Element(e) if f(e)))
+        {
+            Some(i) => i,
+            None => return false,
+        };
+        self.children.remove(i);
+        if i > 0 && matches!(&self.children[i - 1], Node::Text(t) if t.trim().is_empty()) {
+            self.children.remove(i - 1);
+        }
+        true
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            prolog: Vec::new(),
+            root: Element {
+                name: ROOT.to_string(),
+                attributes: Vec::new(),
+                children: vec![Node::Text("\n".to_string())],
+            },
+            epilog: vec![Node::Text("\n".to_string())],
+        }
+    }
+}
+
+impl Config {
+    /// Returns an empty config.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        Ok(std::fs::write(path, self.to_string())?)
+    }
+
+    /// Returns the value of an option, like "full" for `<power v='full'/>`.
+    pub fn option(&self, key: &str) -> Option<&str> {
+        self.root.options().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    /// Returns the options. Options that are not in the file have their default values.
+    pub fn options(&self) -> Result<Options> {
+        from_options(self.root.options())
+    }
+
+    /// Sets an option, adding it if it is not in the file.
+    pub fn options_set<N>(&mut self, key: &str, value: N)
+    where
+        N: std::fmt::Display,
+    {
+        self.root.set_option(key, &value.to_string(), 0);
+    }
+
+    /// Sets an option after checking that its value is valid.
+    pub fn set_option(&mut self, option: OptionKey) -> Result<()> {
+        option.validate()?;
+        self.options_set(&option.key(), option.value());
+        Ok(())
+    }
+
+    /// Removes an option so that the client uses its default value. Returns false if the option
+    /// was not in the file.
+    pub fn remove_option(&mut self, key: &str) -> bool {
+        self.root
+            .remove(|e| e.name == key && e.attribute("v").is_some())
+    }
+
+    pub fn slots(&self) -> Result<Vec<ConfigSlot>> {
+        self.root
+            .elements()
+            .filter(|e| e.name == SLOT)
+            .map(|e| {
+                Ok(ConfigSlot {
+                    id: e.attribute("id").unwrap_or_default().parse()?,
+                    slot_type: e.attribute("type").unwrap_or_default().to_string(),
+                    options: e
+                        .options()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the options of a slot. Options that are not in the file have their default
+    /// values.
+    pub fn slot_options(&self, slot: SlotId) -> Result<SlotOptions> {
+        from_options(self.slot(slot)?.options())
+    }
+
+    /// Sets a slot option, adding it if it is not in the file.
+    pub fn slot_options_set<N>(&mut self, slot: SlotId, key: &str, value: N) -> Result<()>
+    where
+        N: std::fmt::Display,
+    {
+        self.slot_mut(slot)?.set_option(key, &value.to_string(), 1);
+        Ok(())
+    }
+
+    /// Adds a slot with the options of its type. Returns an error if the slot exists.
+    pub fn add_slot(&mut self, slot: SlotId, slot_type: &SlotType) -> Result<()> {
+        if self.slot(slot).is_ok() {
+            return Err(Error::InvalidCommand(format!("slot {} exists", slot)));
+        }
+        let id = slot.0.to_string();
+        let name = slot_type.to_string().to_uppercase();
+        let mut element = Element::new(SLOT, &[("id", &id), ("type", &name)]);
+        for (k, v) in slot_type.options() {
+            element.set_option(k, &v, 1);
+        }
+        self.root.push(element, 0);
+        Ok(())
+    }
+
+    /// Returns false if the slot is not in the file.
+    pub fn remove_slot(&mut self, slot: SlotId) -> bool {
+        self.root.remove(|e| is_slot(e, slot))
+    }
+
+    fn slot(&self, slot: SlotId) -> Result<&Element> {
+        self.root
+            .elements()
+            .find(|e| is_slot(e, slot))
+            .ok_or_else(|| Error::InvalidCommand(format!("no slot {}", slot)))
+    }
+
+    fn slot_mut(&mut self, slot: SlotId) -> Result<&mut Element> {
+        self.root
+            .elements_mut()
+            .find(|e| is_slot(e, slot))
+            .ok_or_else(|| Error::InvalidCommand(format!("no slot {}", slot)))
+    }
+}
+
+fn is_slot(e: &Element, slot: SlotId) -> bool {
+    e.name == SLOT && e.attribute("id").and_then(|id| id.parse().ok()) == Some(slot)
+}
+
+/// Deserializes option elements into `Options` or `SlotOptions`.
+fn from_options<'a, T>(options: impl Iterator<Item = (&'a str, &'a str)>) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let map: serde_json::Map<String, serde_json::Value> =
+        options.map(|(k, v)| (k.to_string(), v.into())).collect();
+    serde_json::from_value(map.into()).map_err(|e| Error::UnexpectedShape(e.to_string()))
+}
+
+impl core::str::FromStr for Config {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let xml_err = |e: quick_xml::Error| Error::UnexpectedShape(e.to_string());
+        let utf8 = |b: &[u8]| Ok::<_, Error>(std::str::from_utf8(b)?.to_string());
+
+        let mut reader = quick_xml::Reader::from_str(s);
+        let mut prolog = Vec::new();
+        let mut epilog = Vec::new();
+        let mut root = None;
+        // Elements that have been started but not ended
+        let mut stack: Vec<Element> = Vec::new();
+
+        loop {
+            let (node, start) = match reader.read_event().map_err(xml_err)? {
+                Event::Eof => break,
+                Event::Start(e) => (Node::Element(element(&e)?), true),
+                Event::Empty(e) => (Node::Element(element(&e)?), false),
+                Event::End(_) => {
+                    let e = stack.pop().expect("end tags are checked by the reader");
+                    (Node::Element(e), false)
+                }
+                Event::Text(e) => (Node::Text(utf8(&e)?), false),
+                Event::Comment(e) => (Node::Comment(utf8(&e)?), false),
+                Event::CData(e) => (Node::Raw(format!("<![CDATA[{}]]>", utf8(&e)?)), false),
+                Event::Decl(e) => (Node::Raw(format!("<?{}?>", utf8(&e)?)), false),
+                Event::PI(e) => (Node::Raw(format!("<?{}?>", utf8(&e)?)), false),
+                Event::DocType(e) => (Node::Raw(format!("<!DOCTYPE{}>", utf8(&e)?)), false),
+            };
+
+            if start {
+                if let Node::Element(e) = node {
+                    stack.push(e);
+                }
+                continue;
+            }
+            match (stack.last_mut(), node) {
+                (Some(parent), node) => parent.children.push(node),
+                (None, Node::Element(e)) if root.is_none() => root = Some(e),
+                (None, Node::Element(e)) => {
+                    return Err(Error::UnexpectedShape(format!(
+                        "more than one root element: {}",
+                        e.name
+                    )))
+                }
+                (None, node) if root.is_none() => prolog.push(node),
+                (None, node) => epilog.push(node),
+            }
+        }
+
+        let root = root.ok_or_else(|| Error::UnexpectedShape("no root element".to_string()))?;
+        if root.name != ROOT {
+            return Err(Error::UnexpectedShape(format!(
+                "root element is {} instead of {}",
+                root.name, ROOT
+            )));
+        }
+        Ok(Self {
+            prolog,
+            root,
+            epilog,
+        })
+    }
+}
+
+fn element(e: &quick_xml::events::BytesStart) -> Result<Element> {
+    let mut element = Element {
+        name: std::str::from_utf8(e.name().as_ref())?.to_string(),
+        ..Default::default()
+    };
+    for attribute in e.attributes() {
+        let attribute = attribute.map_err(|e| Error::UnexpectedShape(e.to_string()))?;
+        let value = attribute
+            .unescape_value()
+            .map_err(|e| Error::UnexpectedShape(e.to_string()))?;
+        element.attributes.push((
+            std::str::from_utf8(attribute.key.as_ref())?.to_string(),
+            value.into_owned(),
+        ));
+    }
+    Ok(element)
+}
+
+impl std::fmt::Display for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for node in &self.prolog {
+            write_node(f, node)?;
+        }
+        write_element(f, &self.root)?;
+        for node in &self.epilog {
+            write_node(f, node)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_node(f: &mut std::fmt::Formatter<'_>, node: &Node) -> std::fmt::Result {
+    match node {
+        Node::Element(e) => write_element(f, e),
+        Node::Text(s) | Node::Raw(s) => write!(f, "{}", s),
+        Node::Comment(s) => write!(f, "<!--{}-->", s),
+    }
+}
+
+/// Writes an element with single-quoted attributes, like the FAH client does.
+fn write_element(f: &mut std::fmt::Formatter<'_>, e: &Element) -> std::fmt::Result {
+    write!(f, "<{}", e.name)?;
+    for (k, v) in &e.attributes {
+        write!(f, " {}='{}'", k, quick_xml::escape::escape(v.as_str()))?;
+    }
+    if e.children.is_empty() {
+        return write!(f, "/>");
+    }
+    write!(f, ">")?;
+    for child in &e.children {
+        write_node(f, child)?;
+    }
+    write!(f, "</{}>", e.name)
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        const CONFIG: &str = "<?xml version='1.0'?>
+<!-- Written by provisioning -->
+<config>
+  <!-- Folding Slot Configuration -->
+  <cause v='COVID_19'/>
+  <user v='Anonymous &amp; friends'/>
+  <team v='0'/>
+  <power v='medium'/>
+  <web-enable v='false'/>
+
+  <!-- Unknown to fahapi -->
+  <extra-settings mode='fast'>
+    <item>text</item>
+  </extra-settings>
+
+  <slot id='0' type='CPU'>
+    <cpus v='4'/>
+    <paused v='true'/>
+  </slot>
+  <slot id='1' type='GPU'/>
+</config>
+";
+        let config: Config = CONFIG.parse().unwrap();
+        assert_eq!(config.to_string(), CONFIG);
+
+        assert_eq!(config.option("user"), Some("Anonymous & friends"));
+        assert_eq!(config.option("extra-settings"), None);
+        let options = config.options().unwrap();
+        assert_eq!(options.cause, "COVID_19");
+        assert_eq!(options.power, Power::PowerMedium);
+        assert_eq!(options.web_enable, StringBool(false));
+        assert_eq!(options.cpu_usage, StringInt(0));
+
+        let slots = config.slots().unwrap();
+        assert_eq!(slots.len(), 2);
+        assert_eq!(slots[0].id, SlotId(0));
+        assert_eq!(slots[0].slot_type, "CPU");
+        assert_eq!(slots[0].options["cpus"], "4");
+        assert_eq!(slots[1].slot_type, "GPU");
+        assert!(slots[1].options.is_empty());
+        assert_eq!(
+            config.slot_options(SlotId(0)).unwrap().paused,
+            StringBool(true)
+        );
+        assert!(config.slot_options(SlotId(2)).is_err());
+
+        let mut config = config;
+        config
+            .set_option(OptionKey::Power(Power::PowerFull))
+            .unwrap();
+        config
+            .set_option(OptionKey::CpuUsage(StringInt(80)))
+            .unwrap();
+        assert!(config
+            .set_option(OptionKey::CpuUsage(StringInt(800)))
+            .is_err());
+        assert!(config.remove_option("team"));
+        assert!(!config.remove_option("team"));
+        config.slot_options_set(SlotId(1), "gpu-index", 0).unwrap();
+        config.slot_options_set(SlotId(0), "cpus", 8).unwrap();
+        assert!(config.slot_options_set(SlotId(2), "cpus", 8).is_err());
+        let cpu = SlotType::Cpu { cpus: None };
+        config.add_slot(SlotId(2), &cpu).unwrap();
+        assert!(config.add_slot(SlotId(2), &cpu).is_err());
+        assert!(config.remove_slot(SlotId(0)));
+
+        let expected = "<?xml version='1.0'?>
+<!-- Written by provisioning -->
+<config>
+  <!-- Folding Slot Configuration -->
+  <cause v='COVID_19'/>
+  <user v='Anonymous &amp; friends'/>
+  <power v='FULL'/>
+  <web-enable v='false'/>
+
+  <!-- Unknown to fahapi -->
+  <extra-settings mode='fast'>
+    <item>text</item>
+  </extra-settings>
+  <slot id='1' type='GPU'>
+    <gpu-index v='0'/>
+  </slot>
+  <cpu-usage v='80'/>
+  <slot id='2' type='CPU'/>
+</config>
+";
+        assert_eq!(config.to_string(), expected);
+        assert_eq!(expected.parse::<Config>().unwrap(), config);
+    }
+
+    #[test]
+    fn test_config_new() {
+        let mut config = Config::new();
+        config.options_set("user", "a'b");
+        config
+            .add_slot(SlotId(0), &SlotType::Cpu { cpus: Some(2) })
+            .unwrap();
+        config.slot_options_set(SlotId(0), "paused", true).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "<config>
+  <user v='a&apos;b'/>
+  <slot id='0' type='CPU'>
+    <cpus v='2'/>
+    <paused v='true'/>
+  </slot>
+</config>
+"
+        );
+
+        assert!("<options/>".parse::<Config>().is_err());
+        assert!("<config><a></config>".parse::<Config>().is_err());
+        assert!("".parse::<Config>().is_err());
+    }
+}
@@ -7,14 +7,37 @@ pub struct Connection {
     pub conn: net::TcpStream,
     pub addr: net::SocketAddr,
     pub connect_timeout: core::time::Duration,
+    /// Password that is sent with the `auth` command after connecting.
+    pub password: Option<String>,
+    /// Read and write timeout of the connection. None blocks forever. Use
+    /// [`set_timeout`](#method.set_timeout) to change it.
+    pub timeout: Option<core::time::Duration>,
 }
 
 impl Connection {
     pub fn connect_timeout(addr: &net::SocketAddr, timeout: core::time::Duration) -> Result<Self> {
         Ok(Self {
-            conn: connect_timeout(addr, timeout)?,
+            conn: connect_timeout(addr, timeout, None, None)?,
             addr: *addr,
             connect_timeout: timeout,
+            password: None,
+            timeout: None,
+        })
+    }
+
+    /// Connects and authenticates with the password. The password is sent again when
+    /// reconnecting.
+    pub fn connect_with_password(
+        addr: &net::SocketAddr,
+        password: &str,
+        timeout: core::time::Duration,
+    ) -> Result<Self> {
+        Ok(Self {
+            conn: connect_timeout(addr, timeout, Some(password), None)?,
+            addr: *addr,
+            connect_timeout: timeout,
+            password: Some(password.to_string()),
+            timeout: None,
         })
     }
 
@@ -29,15 +52,17 @@ impl Connection {
         }
 
         if command.contains('\n') {
-            return Err(Error::msg("command contains newline"));
+            return Err(Error::InvalidCommand(
+                "command contains newline".to_string(),
+            ));
         }
 
         self.conn.write_all(format!("{}\n", command).as_bytes())?;
 
         if let Err(e) = read_message(&mut self.conn, buf) {
             // Try to reconnect on disconnection
-            if e.to_string() == EOF {
-                self.conn = connect_timeout(&self.addr, self.connect_timeout)?;
+            if let Error::Disconnected = e {
+                self.reconnect()?;
             }
             return Err(e);
         }
@@ -45,6 +70,27 @@ impl Connection {
         Ok(())
     }
 
+    /// Replaces the TCP connection with a new one. The password is sent if it is set.
+    pub fn reconnect(&mut self) -> Result<()> {
+        self.conn = connect_timeout(
+            &self.addr,
+            self.connect_timeout,
+            self.password.as_deref(),
+            self.timeout,
+        )?;
+        Ok(())
+    }
+
+    /// Sets the read and write timeout, which is kept when reconnecting. A command that times
+    /// out returns `Error::Timeout`, and its response may still arrive, so reconnect before
+    /// sending more commands.
+    pub fn set_timeout(&mut self, timeout: Option<core::time::Duration>) -> Result<()> {
+        self.timeout = timeout;
+        self.conn.set_read_timeout(timeout)?;
+        self.conn.set_write_timeout(timeout)?;
+        Ok(())
+    }
+
     /// Executes commands which do not return a trailing newline. (Some commands don't end their message
     /// and cause infinite blocking.) The response is written to the buffer.
     pub fn exec_eval(&mut self, command: &str, buf: &mut Vec<u8>) -> Result<()> {
@@ -54,30 +100,68 @@ impl Connection {
             return Ok(());
         }
 
-        self.exec(format!(r#"eval "$({})\n""#, command).as_str(), buf)?;
+        self.exec(eval_command(command).as_str(), buf)?;
+        trim_eval_response(buf);
+        Ok(())
+    }
+}
 
-        // When using eval with a newline, the response contains an extra trailing backslash.
-        if let Some(b) = buf.last() {
-            if *b == b'\\' {
-                buf.pop();
-            }
+/// Wraps a command with `eval` so that its response ends with a newline.
+pub(crate) fn eval_command(command: &str) -> String {
+    format!(r#"eval "$({})\n""#, command)
+}
+
+/// When using eval with a newline, the response contains an extra trailing backslash.
+pub(crate) fn trim_eval_response(buf: &mut Vec<u8>) {
+    if let Some(b) = buf.last() {
+        if *b == b'\\' {
+            buf.pop();
         }
-        Ok(())
     }
 }
 
+/// Connects and reads the welcome message. Reading the welcome message and authenticating also
+/// use the connect timeout, and then `io_timeout` is set on the connection.
 fn connect_timeout(
     addr: &net::SocketAddr,
     timeout: core::time::Duration,
+    password: Option<&str>,
+    io_timeout: Option<core::time::Duration>,
 ) -> Result<net::TcpStream> {
+    use std::io::Write;
+
     let mut conn = net::TcpStream::connect_timeout(addr, timeout)?;
+    conn.set_read_timeout(Some(timeout))?;
+    conn.set_write_timeout(Some(timeout))?;
 
     // Discard welcome message
-    read_message(&mut conn, &mut Vec::new())?;
+    let mut buf: Vec<u8> = Vec::new();
+    read_message(&mut conn, &mut buf)?;
+
+    if let Some(password) = password {
+        conn.write_all(auth_command(password)?.as_bytes())?;
+        read_message(&mut conn, &mut buf)?;
+        check_auth_response(&buf)?;
+    }
+
+    conn.set_read_timeout(io_timeout)?;
+    conn.set_write_timeout(io_timeout)?;
     Ok(conn)
 }
 
-const EOF: &str = "EOF";
+/// Returns the `auth` command including the trailing newline.
+pub(crate) fn auth_command(password: &str) -> Result<String> {
+    Ok(format!("auth {}\n", pyon::to_command_arg(password)?))
+}
+
+/// Returns an error if the client did not accept the password.
+pub(crate) fn check_auth_response(buf: &[u8]) -> Result<()> {
+    let response = std::str::from_utf8(buf)?.trim();
+    if response != "OK" {
+        return Err(Error::AuthRejected(response.to_string()));
+    }
+    Ok(())
+}
 
 pub fn read_message(r: &mut impl std::io::Read, buf: &mut Vec<u8>) -> Result<()> {
     buf.clear();
@@ -86,26 +170,77 @@ pub fn read_message(r: &mut impl std::io::Read, buf: &mut Vec<u8>) -> Result<()>
         if r.read(&mut b)? == 0 {
             // If we haven't reached END_OF_MESSAGE and 0 bytes was read, then EOF was returned.
             // This can occur if the command was invalid.
-            return Err(Error::msg(EOF));
+            return Err(Error::Disconnected);
         }
 
         buf.push(b[0]);
 
-        const END_OF_MESSAGE: &str = "\n> ";
-        if buf.len() >= END_OF_MESSAGE.len()
-            && buf.as_slice()[buf.len() - END_OF_MESSAGE.len()..] == *END_OF_MESSAGE.as_bytes()
-        {
-            buf.truncate(buf.len() - END_OF_MESSAGE.len());
-            if let Some(b) = buf.get(0) {
-                if *b == b'\n' {
-                    buf.drain(..1);
-                }
-            }
+        if trim_end_of_message(buf) {
+            return Ok(());
+        }
+    }
+}
+
+/// Reads the next PyON message that the FAH client pushes, such as `PyON 1 log-update`. Prompts
+/// before the message are skipped. The message is written to the buffer without the trailing
+/// newline.
+pub fn read_pyon_message(r: &mut impl std::io::Read, buf: &mut Vec<u8>) -> Result<()> {
+    buf.clear();
+    loop {
+        let mut b: [u8; 1] = [0];
+        if r.read(&mut b)? == 0 {
+            return Err(Error::Disconnected);
+        }
+
+        buf.push(b[0]);
+
+        if trim_pyon_message(buf) {
             return Ok(());
         }
     }
 }
 
+/// Returns true if buf contains a complete PyON message. Anything before the header and the
+/// newline after the footer are removed from a complete message.
+pub(crate) fn trim_pyon_message(buf: &mut Vec<u8>) -> bool {
+    const END_OF_PYON: &[u8] = b"\n---\n";
+    const HEADER: &[u8] = b"PyON ";
+    if !buf.ends_with(END_OF_PYON) {
+        return false;
+    }
+
+    match buf.windows(HEADER.len()).position(|w| w == HEADER) {
+        Some(start) => {
+            buf.truncate(buf.len() - 1);
+            buf.drain(..start);
+            true
+        }
+        None => {
+            // Not a PyON message
+            buf.clear();
+            false
+        }
+    }
+}
+
+/// Returns true if buf contains a complete message. The prompt and leading newline are removed
+/// from a complete message.
+pub(crate) fn trim_end_of_message(buf: &mut Vec<u8>) -> bool {
+    const END_OF_MESSAGE: &str = "\n> ";
+    if buf.len() >= END_OF_MESSAGE.len()
+        && buf.as_slice()[buf.len() - END_OF_MESSAGE.len()..] == *END_OF_MESSAGE.as_bytes()
+    {
+        buf.truncate(buf.len() - END_OF_MESSAGE.len());
+        if let Some(b) = buf.first() {
+            if *b == b'\n' {
+                buf.drain(..1);
+            }
+        }
+        return true;
+    }
+    false
+}
+
 #[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
@@ -113,6 +248,14 @@ mod tests {
     #[allow(unused_imports)]
     use bytes::Buf;
 
+    #[test]
+    fn test_check_auth_response() {
+        assert!(check_auth_response(b"OK").is_ok());
+        assert!(check_auth_response(b"OK\n").is_ok());
+        assert!(check_auth_response(b"").is_err());
+        assert!(check_auth_response(b"FAILED").is_err());
+    }
+
     #[test]
     fn test_read_message() {
         struct Test {
@@ -121,7 +264,7 @@ mod tests {
             expect_error: bool,
         }
 
-        let tests = vec![
+        let tests = [
             Test {
                 s: b"",
                 expected: b"",
@@ -161,4 +304,49 @@ mod tests {
             assert_eq!(buf.as_slice(), test.expected, "{}", i);
         }
     }
+
+    #[test]
+    fn test_read_pyon_message() {
+        struct Test {
+            s: &'static [u8],
+            expected: &'static [u8],
+            expect_error: bool,
+        }
+
+        let tests = [
+            Test {
+                s: b"",
+                expected: b"",
+                expect_error: true,
+            },
+            Test {
+                s: b"\n> ",
+                expected: b"\n> ",
+                expect_error: true,
+            },
+            Test {
+                s: b"PyON 1 a\n1\n---\n",
+                expected: b"PyON 1 a\n1\n---",
+                expect_error: false,
+            },
+            Test {
+                s: b"\n> \n> PyON 1 a\n\"x\"\n---\nPyON 1 b\n2\n---\n",
+                expected: b"PyON 1 a\n\"x\"\n---",
+                expect_error: false,
+            },
+            Test {
+                s: b"\n---\nPyON 1 a\n1\n---\n",
+                expected: b"PyON 1 a\n1\n---",
+                expect_error: false,
+            },
+        ];
+
+        let mut buf: Vec<u8> = Vec::new();
+        for (i, test) in tests.iter().enumerate() {
+            let result =
+                read_pyon_message(&mut bytes::Bytes::from_static(test.s).reader(), &mut buf);
+            assert_eq!(result.is_err(), test.expect_error, "{}", i);
+            assert_eq!(buf.as_slice(), test.expected, "{}", i);
+        }
+    }
 }
@@ -0,0 +1,114 @@
+/// Errors returned by this crate.
+#[derive(Debug)]
+pub enum Error {
+    /// The FAH client closed the connection. This can occur if the command was invalid. The
+    /// connection is re-established before this error is returned, so the command can be retried.
+    Disconnected,
+    /// Connecting, reading or writing timed out.
+    Timeout,
+    /// The command or one of its arguments cannot be sent to the FAH client.
+    InvalidCommand(String),
+    /// The response is not valid PyON. `position` is the byte offset into `input`.
+    PyonSyntax {
+        input: String,
+        position: usize,
+        message: String,
+    },
+    /// The response was valid but did not have the expected type or format.
+    UnexpectedShape(String),
+    /// An IO error other than a timeout occurred.
+    Io(std::io::Error),
+    /// The FAH client rejected the password. Contains the client's response.
+    AuthRejected(String),
+    /// The response is not valid UTF-8.
+    Utf8(std::str::Utf8Error),
+    /// Any other error.
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Disconnected => write!(f, "disconnected from FAH client"),
+            Error::Timeout => write!(f, "timed out"),
+            Error::InvalidCommand(s) => write!(f, "invalid command: {}", s),
+            Error::PyonSyntax {
+                input,
+                position,
+                message,
+            } => write!(
+                f,
+                "invalid PyON at position {}: {}: {}",
+                position, message, input
+            ),
+            Error::UnexpectedShape(s) => write!(f, "unexpected response: {}", s),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::AuthRejected(s) => write!(f, "password was rejected: {}", s),
+            Error::Utf8(e) => write!(f, "{}", e),
+            Error::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Utf8(e) => Some(e),
+            Error::Other(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            // A read or write timeout returns WouldBlock on Unix and TimedOut on Windows
+            std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => Error::Timeout,
+            _ => Error::Io(e),
+        }
+    }
+}
+
+impl From<std::str::Utf8Error> for Error {
+    fn from(e: std::str::Utf8Error) -> Self {
+        Error::Utf8(e)
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::UnexpectedShape(msg.to_string())
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Other(msg.to_string().into())
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl From<anyhow::Error> for Error {
+    fn from(e: anyhow::Error) -> Self {
+        Error::Other(e.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_from_io_error() {
+        let e: Error = std::io::Error::from(std::io::ErrorKind::WouldBlock).into();
+        assert!(matches!(e, Error::Timeout));
+
+        let e: Error = std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into();
+        assert!(matches!(e, Error::Io(_)));
+    }
+}
@@ -0,0 +1,329 @@
+use super::*;
+use std::net;
+
+/// A FAH client in a [`Fleet`](./struct.Fleet.html).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Host {
+    /// Name that identifies the client in results
+    pub name: String,
+    pub addr: net::SocketAddr,
+    pub password: Option<String>,
+}
+
+impl Host {
+    pub fn new(name: &str, addr: net::SocketAddr) -> Self {
+        Self {
+            name: name.to_string(),
+            addr,
+            password: None,
+        }
+    }
+
+    pub fn with_password(mut self, password: &str) -> Self {
+        self.password = Some(password.to_string());
+        self
+    }
+
+    /// Connects with the timeout, which is also the read and write timeout of the connection.
+    fn connect(&self, timeout: core::time::Duration) -> Result<API> {
+        let mut api = match &self.password {
+            Some(password) => API::connect_with_password(&self.addr, password, timeout)?,
+            None => API::connect_timeout(&self.addr, timeout)?,
+        };
+        api.set_timeout(Some(timeout))?;
+        Ok(api)
+    }
+}
+
+/// Result of a command on one host.
+#[derive(Debug)]
+pub struct HostResult<T> {
+    pub host: String,
+    pub result: Result<T>,
+}
+
+/// Work unit tagged with the host that it belongs to.
+#[derive(Clone, PartialEq, Debug)]
+pub struct HostUnit {
+    pub host: String,
+    pub unit: SlotQueueInfo,
+}
+
+/// Manages many FAH clients. Commands are sent to all hosts concurrently, one thread per host,
+/// and return a [`HostResult`](./struct.HostResult.html) for each host in the order that the
+/// hosts were given. A host that fails does not affect the others.
+///
+/// Hosts that cannot be connected to, or that do not respond within the timeout, are
+/// [unreachable](#method.unreachable). Connecting to them is retried on each command.
+///
+/// Example
+/// ```no_run
+/// let hosts = vec![
+///     fahapi::Host::new("rig1", "192.168.1.2:36330".parse().unwrap()),
+///     fahapi::Host::new("rig2", "192.168.1.3:36330".parse().unwrap()).with_password("secret"),
+/// ];
+/// let mut fleet = fahapi::Fleet::connect(hosts, std::time::Duration::from_secs(5));
+/// for result in fleet.pause_all() {
+///     if let Err(e) = result.result {
+///         println!("{}: {}", result.host, e);
+///     }
+/// }
+/// println!("{} PPD", fleet.total_ppd());
+/// ```
+#[derive(Debug)]
+pub struct Fleet {
+    members: Vec<Member>,
+    timeout: core::time::Duration,
+}
+
+#[derive(Debug)]
+struct Member {
+    host: Host,
+    /// None if the host is unreachable
+    api: Option<API>,
+}
+
+impl Member {
+    fn run<T>(
+        &mut self,
+        f: impl FnOnce(&mut API) -> Result<T>,
+        timeout: core::time::Duration,
+    ) -> Result<T> {
+        if self.api.is_none() {
+            self.api = Some(self.host.connect(timeout)?);
+        }
+
+        let result = f(self.api.as_mut().unwrap());
+        // API reconnects after Disconnected, so only these mean that the host is gone
+        if let Err(Error::Timeout) | Err(Error::Io(_)) = result {
+            self.api = None;
+        }
+        result
+    }
+}
+
+impl Fleet {
+    /// Connects to all hosts concurrently. Hosts that cannot be connected to are unreachable.
+    pub fn connect(hosts: impl IntoIterator<Item = Host>, timeout: core::time::Duration) -> Self {
+        let mut fleet = Self {
+            members: hosts
+                .into_iter()
+                .map(|host| Member { host, api: None })
+                .collect(),
+            timeout,
+        };
+        fleet.run(|_| Ok(()));
+        fleet
+    }
+
+    pub fn hosts(&self) -> impl Iterator<Item = &Host> {
+        self.members.iter().map(|member| &member.host)
+    }
+
+    /// Returns hosts that could not be connected to, or that timed out on the last command.
+    pub fn unreachable(&self) -> Vec<&Host> {
+        self.members
+            .iter()
+            .filter(|member| member.api.is_none())
+            .map(|member| &member.host)
+            .collect()
+    }
+
+    /// Runs `f` on every host concurrently.
+    pub fn run<T, F>(&mut self, f: F) -> Vec<HostResult<T>>
+    where
+        T: Send,
+        F: Fn(&mut API) -> Result<T> + Sync,
+    {
+        let timeout = self.timeout;
+        let f = &f;
+        std::thread::scope(|scope| {
+            let threads: Vec<_> = self
+                .members
+                .iter_mut()
+                .map(|member| {
+                    scope.spawn(move || HostResult {
+                        host: member.host.name.clone(),
+                        result: member.run(f, timeout),
+                    })
+                })
+                .collect();
+            threads
+                .into_iter()
+                .map(|thread| {
+                    thread
+                        .join()
+                        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                })
+                .collect()
+        })
+    }
+
+    /// Pauses all slots on every host.
+    pub fn pause_all(&mut self) -> Vec<HostResult<()>> {
+        self.run(API::pause_all)
+    }
+
+    /// Unpauses all slots on every host.
+    pub fn unpause_all(&mut self) -> Vec<HostResult<()>> {
+        self.run(API::unpause_all)
+    }
+
+    /// Finishes all slots on every host.
+    pub fn finish_all(&mut self) -> Vec<HostResult<()>> {
+        self.run(API::finish_all)
+    }
+
+    /// Sets an option on every host.
+    pub fn options_set<N>(&mut self, key: &str, value: N) -> Vec<HostResult<()>>
+    where
+        N: std::fmt::Display,
+    {
+        let value = value.to_string();
+        self.run(|api| api.options_set(key, &value))
+    }
+
+    /// Sets an option on every host after checking that its value is valid.
+    pub fn set_option(&mut self, option: OptionKey) -> Vec<HostResult<()>> {
+        self.run(|api| api.set_option(option.clone()))
+    }
+
+    pub fn ppd(&mut self) -> Vec<HostResult<f64>> {
+        self.run(API::ppd)
+    }
+
+    /// Returns the sum of the PPD of all hosts that responded.
+    pub fn total_ppd(&mut self) -> f64 {
+        self.ppd()
+            .into_iter()
+            .filter_map(|result| result.result.ok())
+            .sum()
+    }
+
+    pub fn queue_info(&mut self) -> Vec<HostResult<Vec<SlotQueueInfo>>> {
+        self.run(API::queue_info)
+    }
+
+    /// Returns the work units of all hosts that responded.
+    pub fn units(&mut self) -> Vec<HostUnit> {
+        self.queue_info()
+            .into_iter()
+            .filter_map(|result| {
+                let host = result.host;
+                let units = result.result.ok()?;
+                Some(units.into_iter().map(move |unit| HostUnit {
+                    host: host.clone(),
+                    unit,
+                }))
+            })
+            .flatten()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_fleet() {
+        let servers = [
+            testing::MockServer::start().unwrap(),
+            testing::MockServer::start().unwrap(),
+        ];
+        servers[1].state().password = Some("secret".to_string());
+        servers[1].state().ppd = 1000.0;
+
+        // Nothing listens on this address after the listener is dropped
+        let unreachable = net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let hosts = vec![
+            Host::new("a", servers[0].addr()),
+            Host::new("b", servers[1].addr()).with_password("secret"),
+            Host::new("c", unreachable),
+        ];
+        let mut fleet = Fleet::connect(hosts.clone(), core::time::Duration::from_secs(1));
+        assert_eq!(fleet.hosts().cloned().collect::<Vec<Host>>(), hosts);
+        assert_eq!(fleet.unreachable(), vec![&hosts[2]]);
+
+        let results = fleet.pause_all();
+        let names: Vec<&str> = results.iter().map(|r| r.host.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+        assert!(results[0].result.is_ok());
+        assert!(results[1].result.is_ok());
+        assert!(results[2].result.is_err());
+        for server in &servers {
+            assert_eq!(server.state().slots[0].status, SlotStatus::Paused);
+        }
+
+        fleet.unpause_all();
+        fleet.finish_all();
+        for server in &servers {
+            assert_eq!(server.state().slots[0].status, SlotStatus::Finishing);
+        }
+
+        let results = fleet.options_set("power", "FULL");
+        assert!(results[1].result.is_ok());
+        for server in &servers {
+            assert_eq!(server.state().options["power"], "FULL");
+        }
+        let results = fleet.set_option(OptionKey::CpuUsage(StringInt(50)));
+        assert!(results[1].result.is_ok());
+        assert_eq!(servers[0].state().options["cpu-usage"], "50");
+        assert!(fleet
+            .set_option(OptionKey::CpuUsage(StringInt(500)))
+            .iter()
+            .all(|r| r.result.is_err()));
+
+        // The invalid option fails on each host
+        assert!(fleet
+            .options_set("invalid", 0)
+            .iter()
+            .all(|r| r.result.is_err()));
+
+        assert_eq!(fleet.total_ppd(), 249734.0);
+        let units = fleet.units();
+        let names: Vec<&str> = units.iter().map(|u| u.host.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(units[1].unit.project, 13424);
+
+        // A host that goes down does not affect the others
+        let [a, _] = servers;
+        drop(a);
+        assert_eq!(fleet.total_ppd(), 1000.0);
+        assert_eq!(fleet.unreachable(), vec![&hosts[0], &hosts[2]]);
+    }
+
+    #[test]
+    fn test_fleet_timeout() {
+        use std::io::Write;
+
+        let server = testing::MockServer::start().unwrap();
+
+        // Accepts connections and sends the prompt, but never answers commands
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let silent = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut streams = Vec::new();
+            for mut stream in listener.incoming().flatten() {
+                let _ = stream.write_all(b"\n> ");
+                streams.push(stream);
+            }
+        });
+
+        let hosts = vec![Host::new("a", server.addr()), Host::new("b", silent)];
+        let mut fleet = Fleet::connect(hosts.clone(), core::time::Duration::from_millis(200));
+        assert!(fleet.unreachable().is_empty());
+
+        let results = fleet.pause_all();
+        assert!(results[0].result.is_ok());
+        assert!(matches!(results[1].result, Err(Error::Timeout)));
+        assert_eq!(server.state().slots[0].status, SlotStatus::Paused);
+        assert_eq!(fleet.unreachable(), vec![&hosts[1]]);
+        assert_eq!(fleet.total_ppd(), 248734.0);
+    }
+}
@@ -35,7 +35,7 @@ fn test_screensaver() {
 #[test]
 #[cfg_attr(not(feature = "all-tests"), ignore)]
 fn test_always_on() {
-    API_INSTANCE.lock().unwrap().always_on(0).unwrap();
+    API_INSTANCE.lock().unwrap().always_on(SlotId(0)).unwrap();
 }
 
 #[test]
@@ -52,7 +52,7 @@ fn test_do_cycle() {
 #[test]
 #[cfg_attr(not(feature = "all-tests"), ignore)]
 fn test_finish() {
-    API_INSTANCE.lock().unwrap().finish_slot(0).unwrap();
+    API_INSTANCE.lock().unwrap().finish_slot(SlotId(0)).unwrap();
 }
 
 #[test]
@@ -81,7 +81,7 @@ fn test_num_slots() {
 #[test]
 #[cfg_attr(not(feature = "all-tests"), ignore)]
 fn test_on_idle() {
-    API_INSTANCE.lock().unwrap().on_idle(0).unwrap();
+    API_INSTANCE.lock().unwrap().on_idle(SlotId(0)).unwrap();
 }
 
 #[test]
@@ -114,9 +114,9 @@ fn test_options_set_get() {
 fn test_pause_unpause() {
     let mut api = API_INSTANCE.lock().unwrap();
     api.pause_all().unwrap();
-    api.pause_slot(0).unwrap();
+    api.pause_slot(SlotId(0)).unwrap();
     api.unpause_all().unwrap();
-    api.unpause_slot(0).unwrap();
+    api.unpause_slot(SlotId(0)).unwrap();
 }
 
 #[test]
@@ -133,7 +133,11 @@ fn test_queue_info() {
 
 #[test]
 fn test_simulation_info() {
-    API_INSTANCE.lock().unwrap().simulation_info(0).unwrap();
+    API_INSTANCE
+        .lock()
+        .unwrap()
+        .simulation_info(SlotId(0))
+        .unwrap();
 }
 
 #[test]
@@ -145,17 +149,17 @@ fn test_slot_info() {
 #[cfg_attr(not(feature = "all-tests"), ignore)]
 fn test_slot_options_get_set() {
     let mut api = API_INSTANCE.lock().unwrap();
-    assert!(api.slot_options_get(-1).is_err());
+    assert!(api.slot_options_get(SlotId(99)).is_err());
 
-    let old_options = api.slot_options_get(0).unwrap();
+    let old_options = api.slot_options_get(SlotId(0)).unwrap();
     assert!(!old_options.machine_id.is_empty());
 
-    api.slot_options_set(0, "paused", false).unwrap();
+    api.slot_options_set(SlotId(0), "paused", false).unwrap();
 
-    let new_options = api.slot_options_get(0).unwrap();
+    let new_options = api.slot_options_get(SlotId(0)).unwrap();
     assert!(!new_options.paused.0);
 
-    api.slot_options_set(0, "paused", old_options.paused)
+    api.slot_options_set(SlotId(0), "paused", old_options.paused)
         .unwrap();
 }
 
@@ -185,3 +189,53 @@ fn test_exec_eval() {
     api.conn.exec_eval("date", &mut buf).unwrap();
     assert!(!buf.is_empty());
 }
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_help() {
+    let mut api = AsyncAPI::connect_timeout(&DEFAULT_ADDR, core::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert!(!api.help().await.unwrap().is_empty());
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_queue_info() {
+    let mut api = AsyncAPI::connect_timeout(&DEFAULT_ADDR, core::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    api.queue_info().await.unwrap();
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_slot_info() {
+    let mut api = AsyncAPI::connect_timeout(&DEFAULT_ADDR, core::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    api.slot_info().await.unwrap();
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_uptime() {
+    let mut api = AsyncAPI::connect_timeout(&DEFAULT_ADDR, core::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    api.uptime().await.unwrap();
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_async_exec_eval() {
+    let mut api = AsyncAPI::connect_timeout(&DEFAULT_ADDR, core::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    let mut buf: Vec<u8> = Vec::new();
+    api.conn.exec_eval("", &mut buf).await.unwrap();
+    assert!(buf.is_empty());
+
+    api.conn.exec_eval("date", &mut buf).await.unwrap();
+    assert!(!buf.is_empty());
+}
@@ -1,16 +1,52 @@
 //! Folding@home client API wrapper for Rust. Use
 //! [`API::connect_timeout()`](./struct.API.html#method.connect_timeout) to connect to your FAH
-//! client.
+//! client. Enable the `async` feature to use [`AsyncAPI`](./struct.AsyncAPI.html) with tokio.
+//! Enable the `testing` feature to use [`testing::MockServer`](./testing/struct.MockServer.html)
+//! in tests that should not depend on a running FAH client.
 //!
 //! [rust-fahapi on Github](https://github.com/MakotoE/rust-fahapi)
 
+mod apply;
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+mod async_connection;
+#[cfg(feature = "async")]
+mod async_log_stream;
+#[cfg(feature = "config")]
+pub mod config;
 mod connection;
+mod error;
+mod fleet;
+mod log;
+mod log_stream;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod pyon;
+mod snapshot;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "tui")]
+pub mod tui;
 mod types;
-
+mod updates;
+
+pub use apply::*;
+#[cfg(feature = "async")]
+pub use async_api::*;
+#[cfg(feature = "async")]
+pub use async_connection::*;
+#[cfg(feature = "async")]
+pub use async_log_stream::*;
 pub use connection::*;
+pub use error::*;
+pub use fleet::*;
+pub use log::*;
+pub use log_stream::*;
+pub use snapshot::*;
 pub use types::*;
+pub use updates::*;
 
-pub use anyhow::{Error, Result};
 use std::net;
 
 lazy_static::lazy_static! {
@@ -45,13 +81,33 @@ impl API {
         })
     }
 
+    /// Connects to a FAH client that requires a password. The password is sent with the `auth`
+    /// command, and again whenever the connection is re-established.
+    pub fn connect_with_password(
+        addr: &net::SocketAddr,
+        password: &str,
+        timeout: core::time::Duration,
+    ) -> Result<API> {
+        Ok(API {
+            conn: Connection::connect_with_password(addr, password, timeout)?,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Sets the read and write timeout of the connection. By default, commands wait forever for
+    /// a response.
+    pub fn set_timeout(&mut self, timeout: Option<core::time::Duration>) -> Result<()> {
+        self.conn.set_timeout(timeout)
+    }
+
     /// Returns a listing of the FAH API commands.
     pub fn help(&mut self) -> Result<String> {
         self.conn.exec("help", &mut self.buf)?;
-        Ok(std::str::from_utf8(self.buf.as_slice())?.to_string())
+        Ok(std::str::from_utf8(&self.buf)?.to_string())
     }
 
-    /// Enables or disables log updates. Returns current log.
+    /// Enables or disables log updates. Returns current log. Use
+    /// [`LogStream`](./struct.LogStream.html) to receive the log updates that follow.
     pub fn log_updates(&mut self, arg: LogUpdatesArg) -> Result<String> {
         /*
             This command is weird. It returns the log after the next prompt, like this:
@@ -77,16 +133,15 @@ impl API {
     }
 
     /// Sets a slot to be always on.
-    pub fn always_on(&mut self, slot: i64) -> Result<()> {
-        let command = format!("always_on {}", slot);
+    pub fn always_on(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("always_on {}", slot.0);
         self.conn.exec(command.as_str(), &mut self.buf)
     }
 
     /// Returns true if the client has set a user, team or passkey.
     pub fn configured(&mut self) -> Result<bool> {
         self.conn.exec("configured", &mut self.buf)?;
-        let s = std::str::from_utf8(&self.buf)?;
-        Ok(serde_json::from_str(pyon_to_json(&s)?.as_str())?)
+        parse_pyon(&self.buf)
     }
 
     /// Runs one client cycle.
@@ -95,8 +150,8 @@ impl API {
     }
 
     /// Pauses a slot when its current work unit is completed.
-    pub fn finish_slot(&mut self, slot: i64) -> Result<()> {
-        let command = format!("finish {}", slot);
+    pub fn finish_slot(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("finish {}", slot.0);
         self.conn.exec(command.as_str(), &mut self.buf)
     }
 
@@ -108,8 +163,7 @@ impl API {
     /// Returns FAH build and machine info. See `info_struct()`.
     pub fn info(&mut self) -> Result<Vec<Vec<serde_json::Value>>> {
         self.conn.exec("info", &mut self.buf)?;
-        let s = std::str::from_utf8(&self.buf)?;
-        Ok(serde_json::from_str(pyon_to_json(s)?.as_str())?)
+        parse_pyon(&self.buf)
     }
 
     /// Converts Info() data into a structure. Consider this interface to be very unstable.
@@ -120,13 +174,12 @@ impl API {
     /// Returns the number of slots.
     pub fn num_slots(&mut self) -> Result<i64> {
         self.conn.exec("num-slots", &mut self.buf)?;
-        let s = std::str::from_utf8(&self.buf)?;
-        Ok(serde_json::from_str(pyon_to_json(s)?.as_str())?)
+        parse_pyon(&self.buf)
     }
 
     /// Sets a slot to run only when idle.
-    pub fn on_idle(&mut self, slot: i64) -> Result<()> {
-        let command = format!("on_idle {}", slot);
+    pub fn on_idle(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("on_idle {}", slot.0);
         self.conn.exec(command.as_str(), &mut self.buf)
     }
 
@@ -138,25 +191,31 @@ impl API {
     /// Returns the FAH client options.
     pub fn options_get(&mut self) -> Result<Options> {
         self.conn.exec("options -a", &mut self.buf)?;
-        let s = std::str::from_utf8(&self.buf)?;
-        Ok(serde_json::from_str(pyon_to_json(s)?.as_str())?)
+        parse_pyon(&self.buf)
     }
 
-    /// Sets an option.
+    /// Sets an option. The value is quoted if it contains spaces, quotes or other special
+    /// characters.
     pub fn options_set<N>(&mut self, key: &str, value: N) -> Result<()>
     where
         N: std::fmt::Display,
     {
-        let value_str = format!("{}", value);
-
-        if key.contains(&['=', ' ', '!'] as &[char]) || value_str.contains(' ') {
-            return Err(Error::msg(format!(
-                "key or value contains bad character: {}={}",
-                key, value
-            )));
-        }
+        let command = options_set_command(key, value)?;
+        self.conn.exec(command.as_str(), &mut self.buf)
+    }
 
-        let command = format!("options {}={}", key, value_str);
+    /// Sets an option after checking that its value is valid.
+    ///
+    /// Example
+    /// ```no_run
+    /// let mut api = fahapi::API::connect_timeout(
+    ///     &fahapi::DEFAULT_ADDR,
+    ///     std::time::Duration::from_secs(1),
+    /// ).unwrap();
+    /// api.set_option(fahapi::OptionKey::Power(fahapi::Power::PowerFull)).unwrap();
+    /// ```
+    pub fn set_option(&mut self, option: OptionKey) -> Result<()> {
+        let command = set_option_command(&option)?;
         self.conn.exec(command.as_str(), &mut self.buf)
     }
 
@@ -166,24 +225,21 @@ impl API {
     }
 
     /// Pauses a slot.
-    pub fn pause_slot(&mut self, slot: i64) -> Result<()> {
-        let command = format!("pause {}", slot);
+    pub fn pause_slot(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("pause {}", slot.0);
         self.conn.exec(command.as_str(), &mut self.buf)
     }
 
     // Returns the total estimated points per day.
     pub fn ppd(&mut self) -> Result<f64> {
         self.conn.exec("ppd", &mut self.buf)?;
-        let s = std::str::from_utf8(&self.buf)?;
-        Ok(serde_json::from_str(pyon_to_json(s)?.as_str())?)
+        parse_pyon(&self.buf)
     }
 
     /// Returns info about the current work unit.
     pub fn queue_info(&mut self) -> Result<Vec<SlotQueueInfo>> {
         self.conn.exec("queue-info", &mut self.buf)?;
-        let s = std::str::from_utf8(&self.buf)?;
-        let json = pyon_to_json(s)?;
-        serde_json::from_str(&json).map_err(|e| Error::new(e).context(json))
+        parse_pyon(&self.buf)
     }
 
     /// Requests an ID from the assignment server.
@@ -202,41 +258,68 @@ impl API {
     }
 
     /// Returns the simulation information for a slot.
-    pub fn simulation_info(&mut self, slot: i64) -> Result<SimulationInfo> {
+    pub fn simulation_info(&mut self, slot: SlotId) -> Result<SimulationInfo> {
         // "just like the simulations"
-        let command = format!("simulation-info {}", slot);
+        let command = format!("simulation-info {}", slot.0);
         self.conn.exec(command.as_str(), &mut self.buf)?;
-        let s = std::str::from_utf8(&self.buf)?;
-        Ok(serde_json::from_str(pyon_to_json(s)?.as_str())?)
+        parse_pyon(&self.buf)
+    }
+
+    /// Adds a slot. The slot gets the next unused ID and is returned by `slot_info()`.
+    ///
+    /// Example
+    /// ```no_run
+    /// let mut api = fahapi::API::connect_timeout(
+    ///     &fahapi::DEFAULT_ADDR,
+    ///     std::time::Duration::from_secs(1),
+    /// ).unwrap();
+    /// api.slot_add(fahapi::SlotType::Gpu {
+    ///     gpu_index: Some(0),
+    ///     cuda_index: None,
+    ///     opencl_index: None,
+    /// }).unwrap();
+    /// ```
+    pub fn slot_add(&mut self, slot_type: SlotType) -> Result<()> {
+        let command = format!("slot-add {}", slot_args(&slot_type, &[])?);
+        self.conn.exec(command.as_str(), &mut self.buf)
+    }
+
+    /// Changes the type of a slot and sets slot options. The value is quoted if necessary.
+    pub fn slot_modify(
+        &mut self,
+        slot: SlotId,
+        slot_type: SlotType,
+        options: &[(&str, &str)],
+    ) -> Result<()> {
+        let command = format!("slot-modify {} {}", slot.0, slot_args(&slot_type, options)?);
+        self.conn.exec(command.as_str(), &mut self.buf)
     }
 
     /// Deletes a slot.
-    pub fn slot_delete(&mut self, slot: i64) -> Result<()> {
-        let command = format!("slot-delete {}", slot);
+    pub fn slot_delete(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("slot-delete {}", slot.0);
         self.conn.exec(command.as_str(), &mut self.buf)
     }
 
     /// Returns information about each slot.
     pub fn slot_info(&mut self) -> Result<Vec<SlotInfo>> {
         self.conn.exec("slot-info", &mut self.buf)?;
-        let s = std::str::from_utf8(&self.buf)?;
-        Ok(serde_json::from_str(pyon_to_json(s)?.as_str())?)
+        parse_pyon(&self.buf)
     }
 
     /// Returns slot options.
-    pub fn slot_options_get(&mut self, slot: i64) -> Result<SlotOptions> {
-        let command = format!("slot-options {} -a", slot);
+    pub fn slot_options_get(&mut self, slot: SlotId) -> Result<SlotOptions> {
+        let command = format!("slot-options {} -a", slot.0);
         self.conn.exec(command.as_str(), &mut self.buf)?;
-        let s = std::str::from_utf8(&self.buf)?;
-        Ok(serde_json::from_str(pyon_to_json(s)?.as_str())?)
+        parse_pyon(&self.buf)
     }
 
-    /// Sets slot option.
-    pub fn slot_options_set<N>(&mut self, slot: i64, key: &str, value: N) -> Result<()>
+    /// Sets slot option. The value is quoted if necessary.
+    pub fn slot_options_set<N>(&mut self, slot: SlotId, key: &str, value: N) -> Result<()>
     where
         N: std::fmt::Display,
     {
-        let command = format!("slot-options {} {} {}", slot, key, value);
+        let command = slot_options_set_command(slot, key, value)?;
         self.conn.exec(command.as_str(), &mut self.buf)
     }
 
@@ -246,16 +329,15 @@ impl API {
     }
 
     /// Unpauses a slot.
-    pub fn unpause_slot(&mut self, slot: i64) -> Result<()> {
-        let command = format!("unpause {}", slot);
+    pub fn unpause_slot(&mut self, slot: SlotId) -> Result<()> {
+        let command = format!("unpause {}", slot.0);
         self.conn.exec(command.as_str(), &mut self.buf)
     }
 
     /// Returns FAH uptime.
     pub fn uptime(&mut self) -> Result<FAHDuration> {
         self.conn.exec_eval("uptime", &mut self.buf)?;
-        let duration = humantime::parse_duration(std::str::from_utf8(&self.buf)?)?;
-        Ok(chrono::Duration::from_std(duration)?.into())
+        parse_uptime(&self.buf)
     }
 
     /// Blocks until all slots are paused.
@@ -282,6 +364,79 @@ impl std::fmt::Display for LogUpdatesArg {
     }
 }
 
+/// Returns an error if key cannot be used as an option name.
+fn check_option_key(key: &str) -> Result<()> {
+    if key.is_empty() || key.contains(|c: char| c == '=' || c == '!' || c.is_whitespace()) {
+        return Err(Error::InvalidCommand(format!(
+            "key contains bad character: {}",
+            key
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the command for `options_set()`.
+fn options_set_command<N>(key: &str, value: N) -> Result<String>
+where
+    N: std::fmt::Display,
+{
+    check_option_key(key)?;
+    let arg = pyon::to_command_arg(&format!("{}={}", key, value))?;
+    Ok(format!("options {}", arg))
+}
+
+/// Returns the command for `set_option()`.
+fn set_option_command(option: &OptionKey) -> Result<String> {
+    option.validate()?;
+    options_set_command(&option.key(), option.value())
+}
+
+/// Returns the command for `slot_options_set()`.
+fn slot_options_set_command<N>(slot: SlotId, key: &str, value: N) -> Result<String>
+where
+    N: std::fmt::Display,
+{
+    check_option_key(key)?;
+    let value = pyon::to_command_arg(&value.to_string())?;
+    Ok(format!("slot-options {} {} {}", slot.0, key, value))
+}
+
+/// Returns the arguments of `slot-add` and `slot-modify`: the type, then `key=value` for each
+/// option of the type and each of `options`.
+fn slot_args(slot_type: &SlotType, options: &[(&str, &str)]) -> Result<String> {
+    let type_options = slot_type.options();
+    let options = type_options
+        .iter()
+        .map(|(k, v)| (*k, v.as_str()))
+        .chain(options.iter().copied());
+
+    let mut args = vec![slot_type.to_string()];
+    for (k, v) in options {
+        check_option_key(k)?;
+        args.push(pyon::to_command_arg(&format!("{}={}", k, v))?);
+    }
+    Ok(args.join(" "))
+}
+
+/// Parses a PyON response from the FAH client.
+fn parse_pyon<T>(buf: &[u8]) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    pyon::from_message(std::str::from_utf8(buf)?)
+}
+
+/// Parses the response of `eval "$(uptime)\n"`.
+fn parse_uptime(buf: &[u8]) -> Result<FAHDuration> {
+    let duration = humantime::parse_duration(std::str::from_utf8(buf)?)
+        .map_err(|e| Error::UnexpectedShape(e.to_string()))?;
+    Ok(chrono::Duration::from_std(duration)
+        .map_err(|e| Error::UnexpectedShape(e.to_string()))?
+        .into())
+}
+
+/// Returns the log text in a `log-update` message. Use [`LogParser`](./struct.LogParser.html) to
+/// parse the lines of the log.
 pub fn parse_log(s: &str) -> Result<String> {
     // The log looks like this: PyON 1 log-update\n"..."\n---\n\n
     const SUFFIX: &str = "\n---\n\n";
@@ -298,75 +453,29 @@ pub fn parse_log(s: &str) -> Result<String> {
     parse_pyon_string(&removed_suffix[start..])
 }
 
+/// Parses a double quoted PyON string.
 pub fn parse_pyon_string(s: &str) -> Result<String> {
-    if s.len() < 2 || s.bytes().next().unwrap() != b'"' || s.bytes().nth_back(0).unwrap() != b'"' {
-        return Err(Error::msg(format!("cannot parse {}", s)));
-    }
-
-    lazy_static::lazy_static! {
-        static ref MATCH_ESCAPED: regex::Regex = regex::Regex::new(r#"\\x..|\\n|\\r|\\"|\\\\"#).unwrap();
-    }
-
-    let replace_fn: fn(&regex::Captures) -> String = |caps: &regex::Captures| {
-        let capture = &caps[0];
-        if capture.bytes().next().unwrap() == b'\\' {
-            return match capture.bytes().nth(1).unwrap() {
-                b'n' => "\n".to_string(),
-                b'r' => "\r".to_string(),
-                b'"' => "\"".to_string(),
-                b'\\' => "\\".to_string(),
-                b'x' => {
-                    let hex: String = capture.chars().skip(2).collect();
-                    let n = match u32::from_str_radix(hex.as_str(), 16) {
-                        Ok(n) => n,
-                        Err(_) => return capture.to_string(),
-                    };
-
-                    match std::char::from_u32(n) {
-                        Some(c) => c.to_string(),
-                        None => capture.to_string(),
-                    }
-                }
-                _ => capture.to_string(),
-            };
-        }
-
-        capture.to_string()
-    };
-
-    Ok((*MATCH_ESCAPED.replace_all(&s[1..s.len() - 1], replace_fn)).to_string())
+    if !s.starts_with('"') {
+        return Err(Error::PyonSyntax {
+            input: s.to_string(),
+            position: 0,
+            message: "expected quoted string".to_string(),
+        });
+    }
+
+    let mut parser = pyon::Parser::new(s);
+    let result = parser.parse_string()?.into_owned();
+    parser.end()?;
+    Ok(result)
 }
 
+/// Converts a PyON message to JSON. Returns an empty string if the message has no body.
 pub fn pyon_to_json(s: &str) -> Result<String> {
-    // https://pypi.org/project/pon/
-    const PREFIX: &str = "PyON";
-    const SUFFIX: &str = "\n---";
-    if s.len() < PREFIX.len()
-        || s.bytes().take(PREFIX.len()).ne(PREFIX.bytes())
-        || s.len() < SUFFIX.len()
-        || s.bytes().skip(s.len() - SUFFIX.len()).ne(SUFFIX.bytes())
-    {
-        return Err(Error::msg(format!("invalid PyON format: {}", s)));
+    let (start, end) = pyon::message_body(s)?;
+    if s[start..end].trim().is_empty() {
+        return Ok(String::new());
     }
-
-    let mut start = match s.find('\n') {
-        Some(i) => i + 1,
-        None => 0,
-    };
-
-    let end = s.len() - SUFFIX.len();
-    if start > end {
-        start = end;
-    }
-
-    Ok(match &s[start..end] {
-        "True" => "true".to_string(),
-        "False" => "false".to_string(),
-        _ => s[start..end]
-            .replace(": None", r#": """#)
-            .replace(": False", ": false")
-            .replace(": True", ": true"),
-    })
+    Ok(pyon::parse_message(s)?.to_string())
 }
 
 #[cfg(test)]
@@ -382,7 +491,7 @@ mod tests {
             expect_error: bool,
         }
 
-        let tests = vec![
+        let tests = [
             Test {
                 s: "",
                 expected: "",
@@ -437,7 +546,7 @@ mod tests {
             expect_error: bool,
         }
 
-        let tests = vec![
+        let tests = [
             Test {
                 s: "",
                 expected: "",
@@ -469,6 +578,152 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_pyon_error() {
+        let result: Result<Vec<i64>> = parse_pyon(b"PyON 1 units\n[1, x]\n---");
+        match result {
+            Err(Error::PyonSyntax { position, .. }) => assert_eq!(position, 17),
+            _ => panic!("{:?}", result),
+        }
+
+        let result: Result<Vec<i64>> = parse_pyon(b"PyON 1 units\n[\"\"]\n---");
+        assert!(matches!(result, Err(Error::UnexpectedShape(_))));
+
+        let result: Result<Vec<i64>> = parse_pyon(b"\xff");
+        assert!(matches!(result, Err(Error::Utf8(_))));
+    }
+
+    #[test]
+    fn test_parse_pyon_queue_info() {
+        let result: Vec<SlotQueueInfo> = parse_pyon(QUEUE_INFO.as_bytes()).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[1].id, QueueId(1));
+        assert_eq!(result[0].percent_done, Percent(42.17));
+        assert_eq!(result[0].base_credit, StringInt(9405));
+        assert!(result[0].eta.0.is_some());
+    }
+
+    #[test]
+    fn test_options_set_command() {
+        assert_eq!(
+            options_set_command("power", Power::PowerFull).unwrap(),
+            "options power=FULL"
+        );
+        assert_eq!(
+            options_set_command("proxy", "a b").unwrap(),
+            r#"options "proxy=a b""#
+        );
+        assert!(options_set_command("power=", "").is_err());
+        assert!(options_set_command("", "").is_err());
+
+        assert_eq!(
+            slot_options_set_command(SlotId(0), "extra-core-args", "-a \"b\"").unwrap(),
+            r#"slot-options 0 extra-core-args "-a \"b\"""#
+        );
+        assert!(slot_options_set_command(SlotId(0), "a b", "").is_err());
+    }
+
+    #[test]
+    fn test_set_option_command() {
+        struct Test {
+            option: OptionKey,
+            expected: Option<&'static str>,
+        }
+
+        let tests = [
+            Test {
+                option: OptionKey::Power(Power::PowerFull),
+                expected: Some("options power=FULL"),
+            },
+            Test {
+                option: OptionKey::Power(Power::PowerNull),
+                expected: None,
+            },
+            Test {
+                option: OptionKey::CpuUsage(StringInt(100)),
+                expected: Some("options cpu-usage=100"),
+            },
+            Test {
+                option: OptionKey::CpuUsage(StringInt(101)),
+                expected: None,
+            },
+            Test {
+                option: OptionKey::CpuUsage(StringInt(-1)),
+                expected: None,
+            },
+            Test {
+                option: OptionKey::Team(StringInt(0)),
+                expected: Some("options team=0"),
+            },
+            Test {
+                option: OptionKey::Team(StringInt(-5)),
+                expected: None,
+            },
+            Test {
+                option: OptionKey::Paused(StringBool(true)),
+                expected: Some("options paused=true"),
+            },
+            Test {
+                option: OptionKey::User("Anonymous user".to_string()),
+                expected: Some(r#"options "user=Anonymous user""#),
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            assert_eq!(
+                set_option_command(&test.option).ok().as_deref(),
+                test.expected,
+                "{}",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_slot_args() {
+        struct Test {
+            slot_type: SlotType,
+            options: &'static [(&'static str, &'static str)],
+            expected: Option<&'static str>,
+        }
+
+        let tests = [
+            Test {
+                slot_type: SlotType::Cpu { cpus: None },
+                options: &[],
+                expected: Some("cpu"),
+            },
+            Test {
+                slot_type: SlotType::Cpu { cpus: Some(4) },
+                options: &[("paused", "true")],
+                expected: Some("cpu cpus=4 paused=true"),
+            },
+            Test {
+                slot_type: SlotType::Gpu {
+                    gpu_index: Some(0),
+                    cuda_index: None,
+                    opencl_index: Some(1),
+                },
+                options: &[("extra-core-args", "-a b")],
+                expected: Some(r#"gpu gpu-index=0 opencl-index=1 "extra-core-args=-a b""#),
+            },
+            Test {
+                slot_type: SlotType::Cpu { cpus: None },
+                options: &[("a=b", "")],
+                expected: None,
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            assert_eq!(
+                slot_args(&test.slot_type, test.options).ok().as_deref(),
+                test.expected,
+                "{}",
+                i
+            );
+        }
+    }
+
     #[test]
     fn test_pyon_to_json() {
         struct Test {
@@ -477,7 +732,7 @@ mod tests {
             expect_error: bool,
         }
 
-        let tests = vec![
+        let tests = [
             Test {
                 s: "",
                 expected: "",
@@ -510,7 +765,7 @@ mod tests {
             },
             Test {
                 s: "PyON\n{\"\": None}\n---",
-                expected: "{\"\": \"\"}",
+                expected: "{\"\":null}",
                 expect_error: false,
             },
             Test {
@@ -530,13 +785,95 @@ mod tests {
     }
 }
 
-bencher::benchmark_group!(benches, bench_pyon_to_json);
+bencher::benchmark_group!(
+    benches,
+    bench_pyon_to_json,
+    bench_queue_info_pyon_to_json,
+    bench_queue_info_from_message
+);
 bencher::benchmark_main!(benches);
 
 fn bench_pyon_to_json(b: &mut bencher::Bencher) {
-    // test bench_pyon_to_json ... bench:          33 ns/iter (+/- 1)
+    // test bench_pyon_to_json ... bench:         119 ns/iter (+/- 43)
     b.iter(|| pyon_to_json("PyON\nFalse\n---"))
 }
 
+/// queue-info response with two work units.
+const QUEUE_INFO: &str = r#"PyON 1 units
+[
+  {
+    "id": "00",
+    "state": "RUNNING",
+    "error": "NO_ERROR",
+    "project": 13424,
+    "run": 84,
+    "clone": 7,
+    "gen": 37,
+    "core": "0x22",
+    "unit": "0x0000002a287234c95e8a93ff63ec35b1",
+    "percent-done": "42.17%",
+    "eta": "2 hours 13 mins",
+    "ppd": "248734",
+    "credit-estimate": "27510",
+    "waiting-on": "",
+    "next-attempt": "0.00 secs",
+    "time-remaining": "1.91 days",
+    "total-frames": 100,
+    "frames-done": 42,
+    "assigned": "2020-04-20T09:13:27Z",
+    "timeout": "2020-04-21T09:13:27Z",
+    "deadline": "2020-04-23T09:13:27Z",
+    "ws": "155.247.166.219",
+    "cs": "0.0.0.0",
+    "attempts": 0,
+    "slot": "00",
+    "tpf": "2 mins 38 secs",
+    "base-credit": "9405"
+  },
+  {
+    "id": "01",
+    "state": "RUNNING",
+    "error": "NO_ERROR",
+    "project": 13424,
+    "run": 84,
+    "clone": 7,
+    "gen": 37,
+    "core": "0x22",
+    "unit": "0x0000002a287234c95e8a93ff63ec35b1",
+    "percent-done": "42.17%",
+    "eta": "2 hours 13 mins",
+    "ppd": "248734",
+    "credit-estimate": "27510",
+    "waiting-on": "",
+    "next-attempt": "0.00 secs",
+    "time-remaining": "1.91 days",
+    "total-frames": 100,
+    "frames-done": 42,
+    "assigned": "2020-04-20T09:13:27Z",
+    "timeout": "2020-04-21T09:13:27Z",
+    "deadline": "2020-04-23T09:13:27Z",
+    "ws": "155.247.166.219",
+    "cs": "0.0.0.0",
+    "attempts": 0,
+    "slot": "01",
+    "tpf": "2 mins 38 secs",
+    "base-credit": "9405"
+  }
+]
+---"#;
+
+fn bench_queue_info_pyon_to_json(b: &mut bencher::Bencher) {
+    // test bench_queue_info_pyon_to_json ... bench:      33,933 ns/iter (+/- 8,507)
+    b.iter(|| {
+        let json = pyon_to_json(QUEUE_INFO).unwrap();
+        serde_json::from_str::<Vec<SlotQueueInfo>>(&json).unwrap()
+    })
+}
+
+fn bench_queue_info_from_message(b: &mut bencher::Bencher) {
+    // test bench_queue_info_from_message ... bench:      10,575 ns/iter (+/- 1,632)
+    b.iter(|| pyon::from_message::<Vec<SlotQueueInfo>>(QUEUE_INFO).unwrap())
+}
+
 #[cfg(test)]
 mod integration_tests;
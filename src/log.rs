@@ -0,0 +1,497 @@
+use super::*;
+use chrono::{NaiveDate, NaiveTime, TimeZone};
+
+/// A line of the FAH client log, such as
+/// `09:13:31:WARNING:WU00:FS00:0xa7:Completed 0 out of 250000 steps (0%)`.
+///
+/// Example
+/// ```
+/// let line: fahapi::LogLine = "10:45:03:WU00:FS00:Final credit estimate, 27510.00 points"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(line.slot, Some(fahapi::SlotId(0)));
+/// assert_eq!(line.event(), Some(fahapi::LogEvent::FinalCreditEstimate(27510.0)));
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct LogLine {
+    pub time: NaiveTime,
+    /// Date and time in UTC. This is only set by [`LogParser`](./struct.LogParser.html) after it
+    /// has read the date from a `Log Started` or `Date:` line.
+    pub timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    pub level: LogLevel,
+    /// Work queue ID of the `WU01` prefix
+    pub queue_id: Option<QueueId>,
+    /// Folding slot ID of the `FS00` prefix
+    pub slot: Option<SlotId>,
+    /// Core of the `0xa7` prefix, for lines that were written by the core
+    pub core: Option<String>,
+    pub message: String,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum LogLevel {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+/// Event that a log line reports. See [`LogLine::event()`](./struct.LogLine.html#method.event).
+#[derive(Clone, PartialEq, Debug)]
+pub enum LogEvent {
+    /// `Received Unit: id:00 state:DOWNLOAD error:NO_ERROR project:13424 run:84 clone:7 gen:37 ...`
+    UnitAssigned(PRCG),
+    /// `Sending unit results: id:00 state:SEND error:NO_ERROR ...`
+    SendingResults,
+    /// `Final credit estimate, 27510.00 points`
+    FinalCreditEstimate(f64),
+    /// `Sending unit results` with an error such as `FAULTY`, which is sent after the core fails.
+    UnitFailed { error: String },
+    /// `FahCore returned: FINISHED_UNIT (100 = 0x64)`
+    CoreReturned { status: String, code: i64 },
+}
+
+impl LogLine {
+    /// Returns the event that this line reports, or None if it is not a recognized event.
+    pub fn event(&self) -> Option<LogEvent> {
+        if self.core.is_some() {
+            return None;
+        }
+
+        let message = self.message.as_str();
+        if let Some(fields) = message.strip_prefix("Received Unit: ") {
+            Some(LogEvent::UnitAssigned(unit_prcg(fields)?))
+        } else if let Some(fields) = message.strip_prefix("Sending unit results: ") {
+            match unit_field(fields, "error") {
+                None | Some("NO_ERROR") => Some(LogEvent::SendingResults),
+                Some(error) => Some(LogEvent::UnitFailed {
+                    error: error.to_string(),
+                }),
+            }
+        } else if let Some(s) = message.strip_prefix("Final credit estimate, ") {
+            Some(LogEvent::FinalCreditEstimate(
+                s.strip_suffix(" points")?.parse().ok()?,
+            ))
+        } else if let Some(s) = message.strip_prefix("FahCore returned: ") {
+            // FINISHED_UNIT (100 = 0x64)
+            let (status, code) = s.split_once(" (")?;
+            let code = code.split(' ').next()?.parse().ok()?;
+            Some(LogEvent::CoreReturned {
+                status: status.to_string(),
+                code,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the work unit that this line mentions. It is found in `Received Unit` and
+    /// `Sending unit results` lines, and in `Project: 13424 (Run 84, Clone 7, Gen 37)` lines that
+    /// the core writes.
+    pub fn prcg(&self) -> Option<PRCG> {
+        let message = self.message.as_str();
+        if let Some(fields) = message
+            .strip_prefix("Received Unit: ")
+            .or_else(|| message.strip_prefix("Sending unit results: "))
+        {
+            unit_prcg(fields)
+        } else if let Some(s) = message.strip_prefix("Project: ") {
+            // 13424 (Run 84, Clone 7, Gen 37)
+            let (project, s) = s.split_once(" (Run ")?;
+            let (run, s) = s.split_once(", Clone ")?;
+            let (clone, s) = s.split_once(", Gen ")?;
+            let gen = s.strip_suffix(')')?;
+            Some(PRCG {
+                project: project.parse().ok()?,
+                run: run.parse().ok()?,
+                clone: clone.parse().ok()?,
+                gen: gen.parse().ok()?,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the PRCG of a unit description like `id:00 project:13424 run:84 clone:7 gen:37`.
+fn unit_prcg(fields: &str) -> Option<PRCG> {
+    let field = |name: &str| unit_field(fields, name)?.parse().ok();
+    Some(PRCG {
+        project: field("project")?,
+        run: field("run")?,
+        clone: field("clone")?,
+        gen: field("gen")?,
+    })
+}
+
+/// Returns the value of a `name:value` field in a unit description.
+fn unit_field<'a>(fields: &'a str, name: &str) -> Option<&'a str> {
+    fields.split(' ').find_map(|field| {
+        let (k, v) = field.split_once(':')?;
+        if k == name {
+            Some(v)
+        } else {
+            None
+        }
+    })
+}
+
+impl core::str::FromStr for LogLine {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim_end_matches(&['\r', '\n'] as &[char]);
+        let err = || Error::UnexpectedShape(format!("not a log line: {}", s));
+
+        // The time is followed by a colon
+        if s.len() < 9 || !s.is_char_boundary(8) || s.as_bytes()[8] != b':' {
+            return Err(err());
+        }
+        let time = NaiveTime::parse_from_str(&s[..8], "%H:%M:%S").map_err(|_| err())?;
+        let mut rest = &s[9..];
+
+        let mut level = LogLevel::Info;
+        if let Some(s) = rest.strip_prefix("WARNING:") {
+            level = LogLevel::Warning;
+            rest = s;
+        } else if let Some(s) = rest.strip_prefix("ERROR:") {
+            level = LogLevel::Error;
+            rest = s;
+        }
+
+        let queue_id = strip_id(&mut rest, "WU").map(QueueId);
+        let slot = strip_id(&mut rest, "FS").map(SlotId);
+
+        let mut core = None;
+        if let Some((prefix, s)) = rest.split_once(':') {
+            if prefix.len() > 2
+                && prefix.starts_with("0x")
+                && prefix[2..].bytes().all(|b| b.is_ascii_hexdigit())
+            {
+                core = Some(prefix.to_string());
+                rest = s;
+            }
+        }
+
+        Ok(Self {
+            time,
+            timestamp: None,
+            level,
+            queue_id,
+            slot,
+            core,
+            message: rest.to_string(),
+        })
+    }
+}
+
+/// Removes a prefix such as `WU01:` from s and returns the ID.
+fn strip_id(s: &mut &str, prefix: &str) -> Option<u32> {
+    let rest = s.strip_prefix(prefix)?;
+    let (id, rest) = rest.split_once(':')?;
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let id = id.parse().ok()?;
+    *s = rest;
+    Some(id)
+}
+
+/// Parses log lines and sets their timestamps using the date from previous lines. Use one parser
+/// for each log, such as the output of
+/// [`API::log_updates()`](./struct.API.html#method.log_updates), the items of a
+/// [`LogStream`](./struct.LogStream.html) or a `log.txt` file.
+///
+/// Example
+/// ```no_run
+/// let file = std::io::BufReader::new(std::fs::File::open("log.txt").unwrap());
+/// for line in fahapi::LogParser::new().read(file) {
+///     let line = line.unwrap();
+///     if let Some(event) = line.event() {
+///         println!("{:?} {:?}", line.timestamp, event);
+///     }
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct LogParser {
+    date: Option<NaiveDate>,
+}
+
+impl LogParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a line. Returns None for lines that are not log lines, such as the `Date:` lines
+    /// that the client writes at midnight.
+    pub fn parse_line(&mut self, line: &str) -> Option<LogLine> {
+        let mut log_line: LogLine = match line.parse() {
+            Ok(log_line) => log_line,
+            Err(_) => {
+                if let Some(date) = banner_date(line) {
+                    self.date = Some(date);
+                }
+                return None;
+            }
+        };
+
+        log_line.timestamp = self
+            .date
+            .map(|date| chrono::Utc.from_utc_datetime(&date.and_time(log_line.time)));
+        Some(log_line)
+    }
+
+    /// Parses every line of the string. Chunks of a log should contain whole lines.
+    pub fn parse<'a>(&'a mut self, s: &'a str) -> impl Iterator<Item = LogLine> + 'a {
+        s.lines().filter_map(move |line| self.parse_line(line))
+    }
+
+    /// Parses lines from a reader such as a `log.txt` file.
+    pub fn read(mut self, r: impl std::io::BufRead) -> impl Iterator<Item = Result<LogLine>> {
+        r.lines().filter_map(move |line| match line {
+            Ok(line) => self.parse_line(&line).map(Ok),
+            Err(e) => Some(Err(e.into())),
+        })
+    }
+}
+
+/// Returns the date of a line like
+/// `*********************** Log Started 2020-04-20T09:13:27Z ***********************` or
+/// `******************************* Date: 2020-04-20 *******************************`.
+fn banner_date(line: &str) -> Option<NaiveDate> {
+    let s = line.trim().trim_matches('*').trim();
+    let date = s
+        .strip_prefix("Log Started ")
+        .or_else(|| s.strip_prefix("Date: "))?;
+    NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_log_line_from_str() {
+        struct Test {
+            s: &'static str,
+            level: LogLevel,
+            queue_id: Option<QueueId>,
+            slot: Option<SlotId>,
+            core: Option<&'static str>,
+            message: &'static str,
+        }
+
+        let tests = [
+            Test {
+                s: "09:13:27:Trying to access database...",
+                level: LogLevel::Info,
+                queue_id: None,
+                slot: None,
+                core: None,
+                message: "Trying to access database...",
+            },
+            Test {
+                s: "09:13:29:WU00:FS00:Connecting to 65.254.110.245:8080\r\n",
+                level: LogLevel::Info,
+                queue_id: Some(QueueId(0)),
+                slot: Some(SlotId(0)),
+                core: None,
+                message: "Connecting to 65.254.110.245:8080",
+            },
+            Test {
+                s: "09:13:32:WU01:FS01:0x22:Completed 0 out of 2500000 steps (0%)",
+                level: LogLevel::Info,
+                queue_id: Some(QueueId(1)),
+                slot: Some(SlotId(1)),
+                core: Some("0x22"),
+                message: "Completed 0 out of 2500000 steps (0%)",
+            },
+            Test {
+                s: "09:13:32:WARNING:WU01:FS00:Failed to get assignment: No WUs available",
+                level: LogLevel::Warning,
+                queue_id: Some(QueueId(1)),
+                slot: Some(SlotId(0)),
+                core: None,
+                message: "Failed to get assignment: No WUs available",
+            },
+            Test {
+                s: "09:13:32:ERROR:FS01:Exception: 0x:",
+                level: LogLevel::Error,
+                queue_id: None,
+                slot: Some(SlotId(1)),
+                core: None,
+                message: "Exception: 0x:",
+            },
+            Test {
+                s: "09:13:32:",
+                level: LogLevel::Info,
+                queue_id: None,
+                slot: None,
+                core: None,
+                message: "",
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let line: LogLine = test.s.parse().unwrap();
+            assert_eq!(line.level, test.level, "{}", i);
+            assert_eq!(line.queue_id, test.queue_id, "{}", i);
+            assert_eq!(line.slot, test.slot, "{}", i);
+            assert_eq!(line.core.as_deref(), test.core, "{}", i);
+            assert_eq!(line.message, test.message, "{}", i);
+            assert_eq!(line.timestamp, None, "{}", i);
+        }
+
+        assert!("".parse::<LogLine>().is_err());
+        assert!("09:13:2".parse::<LogLine>().is_err());
+        assert!("99:13:27:a".parse::<LogLine>().is_err());
+        assert!("**** Date: 2020-04-20 ****".parse::<LogLine>().is_err());
+    }
+
+    #[test]
+    fn test_log_line_event() {
+        struct Test {
+            s: &'static str,
+            expected: Option<LogEvent>,
+        }
+
+        let tests = [
+            Test {
+                s: "09:13:31:WU00:FS00:Received Unit: id:00 state:DOWNLOAD error:NO_ERROR \
+                    project:13424 run:84 clone:7 gen:37 core:0xa7 unit:0x0000002a287234c95e8a93ff",
+                expected: Some(LogEvent::UnitAssigned(PRCG {
+                    project: 13424,
+                    run: 84,
+                    clone: 7,
+                    gen: 37,
+                })),
+            },
+            Test {
+                s: "10:45:02:WU00:FS00:Sending unit results: id:00 state:SEND error:NO_ERROR \
+                    project:13424 run:84 clone:7 gen:37 core:0xa7",
+                expected: Some(LogEvent::SendingResults),
+            },
+            Test {
+                s: "10:45:02:WU00:FS00:Sending unit results: id:00 state:SEND error:FAULTY \
+                    project:13424 run:84 clone:7 gen:37 core:0xa7",
+                expected: Some(LogEvent::UnitFailed {
+                    error: "FAULTY".to_string(),
+                }),
+            },
+            Test {
+                s: "10:45:03:WU00:FS00:Final credit estimate, 27510.00 points",
+                expected: Some(LogEvent::FinalCreditEstimate(27510.0)),
+            },
+            Test {
+                s: "10:45:02:WU00:FS00:FahCore returned: FINISHED_UNIT (100 = 0x64)",
+                expected: Some(LogEvent::CoreReturned {
+                    status: "FINISHED_UNIT".to_string(),
+                    code: 100,
+                }),
+            },
+            Test {
+                s: "10:45:02:WARNING:WU00:FS01:FahCore returned: BAD_WORK_UNIT (114 = 0x72)",
+                expected: Some(LogEvent::CoreReturned {
+                    status: "BAD_WORK_UNIT".to_string(),
+                    code: 114,
+                }),
+            },
+            Test {
+                s: "10:45:02:WU00:FS00:Received Unit: id:00",
+                expected: None,
+            },
+            Test {
+                s: "10:45:02:WU00:FS00:0xa7:Final credit estimate, 1.00 points",
+                expected: None,
+            },
+            Test {
+                s: "10:45:03:WU00:FS00:Upload complete",
+                expected: None,
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let line: LogLine = test.s.parse().unwrap();
+            assert_eq!(line.event(), test.expected, "{}", i);
+        }
+    }
+
+    #[test]
+    fn test_log_line_prcg() {
+        struct Test {
+            s: &'static str,
+            expected: Option<&'static str>,
+        }
+
+        let tests = [
+            Test {
+                s: "09:13:31:WU00:FS00:Received Unit: id:00 state:DOWNLOAD error:NO_ERROR \
+                    project:13424 run:84 clone:7 gen:37 core:0xa7 unit:0x0000002a287234c95e8a93ff",
+                expected: Some("P13424 R84 C7 G37"),
+            },
+            Test {
+                s: "10:45:02:WU00:FS00:Sending unit results: id:00 state:SEND error:NO_ERROR \
+                    project:13424 run:84 clone:7 gen:37 core:0xa7",
+                expected: Some("P13424 R84 C7 G37"),
+            },
+            Test {
+                s: "09:13:33:WU00:FS00:0xa7:Project: 13424 (Run 84, Clone 7, Gen 37)",
+                expected: Some("P13424 R84 C7 G37"),
+            },
+            Test {
+                s: "09:13:33:WU00:FS00:0xa7:Project: 13424 (Run 84, Clone 7)",
+                expected: None,
+            },
+            Test {
+                s: "10:45:03:WU00:FS00:Final credit estimate, 27510.00 points",
+                expected: None,
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let line: LogLine = test.s.parse().unwrap();
+            let expected = test.expected.map(|s| s.parse().unwrap());
+            assert_eq!(line.prcg(), expected, "{}", i);
+        }
+    }
+
+    #[test]
+    fn test_log_parser() {
+        const LOG: &str = "\
+*********************** Log Started 2020-04-20T09:13:27Z ***********************
+09:13:27:************************* Folding@home Client *************************
+23:59:59:WU00:FS00:0xa7:Completed 2500 out of 250000 steps (1%)
+******************************* Date: 2020-04-21 *******************************
+00:00:01:WU00:FS00:0xa7:Completed 5000 out of 250000 steps (2%)
+";
+
+        let mut parser = LogParser::new();
+        assert!(parser
+            .parse_line("09:13:27:Trying to access database...")
+            .unwrap()
+            .timestamp
+            .is_none());
+
+        let lines: Vec<LogLine> = parser.parse(LOG).collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0].timestamp.unwrap().to_rfc3339(),
+            "2020-04-20T09:13:27+00:00"
+        );
+        assert_eq!(
+            lines[1].timestamp.unwrap().to_rfc3339(),
+            "2020-04-20T23:59:59+00:00"
+        );
+        assert_eq!(
+            lines[2].timestamp.unwrap().to_rfc3339(),
+            "2020-04-21T00:00:01+00:00"
+        );
+
+        let lines: Vec<LogLine> = LogParser::new()
+            .read(LOG.as_bytes())
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2].message, "Completed 5000 out of 250000 steps (2%)");
+    }
+}
@@ -0,0 +1,236 @@
+use super::*;
+use std::net;
+
+/// Iterator over the log text that the FAH client pushes after `log-updates start`. It owns a
+/// dedicated connection, so it can be used alongside an [`API`](./struct.API.html) that is
+/// connected to the same client.
+///
+/// The first item is the current log, and each item after that is the text that was appended to
+/// the log since the previous item. `next()` blocks until the client sends more text. If the
+/// connection is lost, the stream reconnects and sends `log-updates restart`. Text that was
+/// already returned is skipped, unless the new log is shorter than the text returned so far (for
+/// example, because the client was restarted).
+///
+/// Example
+/// ```no_run
+/// let mut stream = fahapi::LogStream::connect_timeout(
+///     &fahapi::DEFAULT_ADDR,
+///     std::time::Duration::from_secs(1),
+/// ).unwrap();
+/// for chunk in stream.by_ref().take(3) {
+///     print!("{}", chunk.unwrap());
+/// }
+/// stream.stop().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct LogStream {
+    pub conn: Connection,
+    buf: Vec<u8>,
+    received: usize,
+    stopped: bool,
+}
+
+impl LogStream {
+    /// Connects to your FAH client with a timeout and starts log updates.
+    pub fn connect_timeout(addr: &net::SocketAddr, timeout: core::time::Duration) -> Result<Self> {
+        Self::from_connection(Connection::connect_timeout(addr, timeout)?)
+    }
+
+    /// Connects to a FAH client that requires a password and starts log updates.
+    pub fn connect_with_password(
+        addr: &net::SocketAddr,
+        password: &str,
+        timeout: core::time::Duration,
+    ) -> Result<Self> {
+        Self::from_connection(Connection::connect_with_password(addr, password, timeout)?)
+    }
+
+    /// Starts log updates on the connection. The connection should not be used for other
+    /// commands.
+    pub fn from_connection(mut conn: Connection) -> Result<Self> {
+        use std::io::Write;
+
+        conn.conn
+            .write_all(format!("log-updates {}\n", LogUpdatesArg::Start).as_bytes())?;
+        Ok(Self {
+            conn,
+            buf: Vec::new(),
+            received: 0,
+            stopped: false,
+        })
+    }
+
+    /// Sends `log-updates stop` and waits for the client to acknowledge it. The iterator returns
+    /// `None` after this.
+    pub fn stop(&mut self) -> Result<()> {
+        use std::io::Write;
+
+        self.stopped = true;
+        self.conn
+            .conn
+            .write_all(format!("log-updates {}\n", LogUpdatesArg::Stop).as_bytes())?;
+        read_message(&mut self.conn.conn, &mut self.buf)
+    }
+
+    fn restart(&mut self) -> Result<()> {
+        use std::io::Write;
+
+        self.conn.reconnect()?;
+        self.conn
+            .conn
+            .write_all(format!("log-updates {}\n", LogUpdatesArg::Restart).as_bytes())?;
+        Ok(())
+    }
+}
+
+impl Iterator for LogStream {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.stopped {
+            if let Err(e) = read_pyon_message(&mut self.conn.conn, &mut self.buf) {
+                if let Error::Disconnected = e {
+                    match self.restart() {
+                        Ok(()) => continue,
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+                return Some(Err(e));
+            }
+
+            match log_chunk(&self.buf, &mut self.received) {
+                Ok(Some(chunk)) => return Some(Ok(chunk)),
+                Ok(None) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+impl Drop for LogStream {
+    fn drop(&mut self) {
+        use std::io::Write;
+
+        if !self.stopped {
+            // The connection is closed anyway, so the response is not needed
+            let _ = self
+                .conn
+                .conn
+                .write_all(format!("log-updates {}\n", LogUpdatesArg::Stop).as_bytes());
+        }
+    }
+}
+
+/// Returns the new log text in a `log-restart` or `log-update` message. `received` is the length
+/// of the log that was returned so far. Returns None for other messages and if there is no new
+/// text.
+pub(crate) fn log_chunk(buf: &[u8], received: &mut usize) -> Result<Option<String>> {
+    let message = std::str::from_utf8(buf)?;
+    let chunk = match pyon::message_name(message)? {
+        "log-restart" => {
+            let log: String = pyon::from_message(message)?;
+            let skip = if log.len() >= *received && log.is_char_boundary(*received) {
+                *received
+            } else {
+                0
+            };
+            *received = log.len();
+            log[skip..].to_string()
+        }
+        "log-update" => {
+            let chunk: String = pyon::from_message(message)?;
+            *received += chunk.len();
+            chunk
+        }
+        _ => return Ok(None),
+    };
+
+    if chunk.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(chunk))
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_log_chunk() {
+        struct Test {
+            message: &'static str,
+            expected: Option<&'static str>,
+            received: usize,
+        }
+
+        let tests = [
+            Test {
+                message: "PyON 1 log-restart\n\"ab\"\n---",
+                expected: Some("ab"),
+                received: 2,
+            },
+            Test {
+                message: "PyON 1 log-update\n\"c\\nd\"\n---",
+                expected: Some("c\nd"),
+                received: 5,
+            },
+            Test {
+                message: "PyON 1 units\n[]\n---",
+                expected: None,
+                received: 5,
+            },
+            // Reconnected
+            Test {
+                message: "PyON 1 log-restart\n\"abc\\nde\"\n---",
+                expected: Some("e"),
+                received: 6,
+            },
+            Test {
+                message: "PyON 1 log-restart\n\"abc\\nde\"\n---",
+                expected: None,
+                received: 6,
+            },
+            // Client restarted
+            Test {
+                message: "PyON 1 log-restart\n\"x\"\n---",
+                expected: Some("x"),
+                received: 1,
+            },
+        ];
+
+        let mut received = 0;
+        for (i, test) in tests.iter().enumerate() {
+            let result = log_chunk(test.message.as_bytes(), &mut received).unwrap();
+            assert_eq!(result.as_deref(), test.expected, "{}", i);
+            assert_eq!(received, test.received, "{}", i);
+        }
+
+        assert!(log_chunk(b"PyON 1 log-update\n1\n---", &mut received).is_err());
+    }
+
+    #[test]
+    fn test_log_stream() {
+        let server = testing::MockServer::start().unwrap();
+        server.state().log = "a\n".to_string();
+
+        let mut stream =
+            LogStream::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+        assert_eq!(stream.next().unwrap().unwrap(), "a\n");
+        server.append_log("b\n").unwrap();
+        assert_eq!(stream.next().unwrap().unwrap(), "b\n");
+
+        // Only the text that was missed is returned after reconnecting
+        server.disconnect();
+        server.append_log("c\n").unwrap();
+        assert_eq!(stream.next().unwrap().unwrap(), "c\n");
+
+        stream.stop().unwrap();
+        assert!(stream.next().is_none());
+        assert_eq!(
+            server.state().commands.last().unwrap().as_str(),
+            "log-updates stop"
+        );
+    }
+}
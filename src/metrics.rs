@@ -0,0 +1,407 @@
+//! Prometheus exporter for the FAH client. Enable the `metrics` feature to use this module.
+//!
+//! Example
+//! ```no_run
+//! let listener = std::net::TcpListener::bind("0.0.0.0:9659").unwrap();
+//! let mut exporter = fahapi::metrics::Exporter::connect(
+//!     &fahapi::DEFAULT_ADDR,
+//!     None,
+//!     std::time::Duration::from_secs(1),
+//! );
+//! exporter.serve(&listener).unwrap();
+//! ```
+
+use super::*;
+use std::fmt::Write as _;
+use std::io::{BufRead, Write};
+use std::net;
+
+const UP_HELP: &str = "Whether the FAH client responded";
+/// Read and write timeout of the connection to the client, so that a client that stops
+/// responding does not block scrapes.
+const CLIENT_TIMEOUT: core::time::Duration = core::time::Duration::from_secs(10);
+
+/// Values that are exported, collected from one FAH client.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Metrics {
+    pub ppd: f64,
+    pub uptime: FAHDuration,
+    pub slots: Vec<SlotInfo>,
+    pub units: Vec<SlotQueueInfo>,
+}
+
+impl Metrics {
+    /// Gets the slots, queue, PPD and uptime from the client.
+    pub fn collect(api: &mut API) -> Result<Self> {
+        Ok(Self {
+            slots: api.slot_info()?,
+            units: api.queue_info()?,
+            ppd: api.ppd()?,
+            uptime: api.uptime()?,
+        })
+    }
+
+    /// Returns the metrics in the Prometheus text format.
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        write_gauge(&mut out, "fah_up", UP_HELP, [(String::new(), 1.0)]);
+        write_gauge(
+            &mut out,
+            "fah_ppd",
+            "Estimated points per day",
+            [(String::new(), self.ppd)],
+        );
+        if let Some(uptime) = self.uptime.0 {
+            write_gauge(
+                &mut out,
+                "fah_uptime_seconds",
+                "Time since the FAH client started",
+                [(String::new(), uptime.num_seconds() as f64)],
+            );
+        }
+
+        write_gauge(
+            &mut out,
+            "fah_slot_paused",
+            "Whether the slot is paused",
+            self.slots.iter().map(|slot| {
+                let paused = if slot.status.is_paused() { 1.0 } else { 0.0 };
+                (labels(&[("slot", &slot.id.to_string())]), paused)
+            }),
+        );
+
+        let units: Vec<(String, &SlotQueueInfo)> = self
+            .units
+            .iter()
+            .map(|unit| (unit_labels(unit), unit))
+            .collect();
+        write_gauge(
+            &mut out,
+            "fah_wu_percent_done",
+            "Progress of the work unit in percent",
+            units
+                .iter()
+                .map(|(labels, unit)| (labels.clone(), unit.percent_done.0)),
+        );
+        write_gauge(
+            &mut out,
+            "fah_wu_eta_seconds",
+            "Estimated time until the work unit is finished",
+            units.iter().filter_map(|(labels, unit)| {
+                Some((labels.clone(), unit.eta.0?.num_seconds() as f64))
+            }),
+        );
+        write_gauge(
+            &mut out,
+            "fah_wu_credit_estimate",
+            "Estimated credit for the work unit",
+            units
+                .iter()
+                .map(|(labels, unit)| (labels.clone(), unit.credit_estimate.0 as f64)),
+        );
+        write_gauge(
+            &mut out,
+            "fah_wu_attempts",
+            "Number of attempts to download or upload the work unit",
+            units
+                .iter()
+                .map(|(labels, unit)| (labels.clone(), unit.attempts as f64)),
+        );
+        out
+    }
+}
+
+/// Serves `/metrics` over HTTP. The metrics are collected from the client on each request. If
+/// the client does not respond, only `fah_up 0` is exported, and the exporter reconnects on the
+/// next request.
+#[derive(Debug)]
+pub struct Exporter {
+    /// None if the client could not be connected to, or if the connection may be unusable after
+    /// the last request failed
+    pub api: Option<API>,
+    addr: net::SocketAddr,
+    password: Option<String>,
+    connect_timeout: core::time::Duration,
+}
+
+impl Exporter {
+    /// Sets a read and write timeout on the connection.
+    pub fn new(mut api: API) -> Self {
+        // The timeout is kept even if setting it fails, and is set again when reconnecting
+        let _ = api.set_timeout(Some(CLIENT_TIMEOUT));
+        Self {
+            addr: api.conn.addr,
+            password: api.conn.password.clone(),
+            connect_timeout: api.conn.connect_timeout,
+            api: Some(api),
+        }
+    }
+
+    /// Connects to the client, which may be down. The exporter starts without a connection if
+    /// connecting fails, and connecting is retried on each request.
+    pub fn connect(
+        addr: &net::SocketAddr,
+        password: Option<&str>,
+        timeout: core::time::Duration,
+    ) -> Self {
+        let mut exporter = Self {
+            api: None,
+            addr: *addr,
+            password: password.map(str::to_string),
+            connect_timeout: timeout,
+        };
+        exporter.api = exporter.connect_api().ok();
+        exporter
+    }
+
+    fn connect_api(&self) -> Result<API> {
+        let mut api = match &self.password {
+            Some(password) => {
+                API::connect_with_password(&self.addr, password, self.connect_timeout)?
+            }
+            None => API::connect_timeout(&self.addr, self.connect_timeout)?,
+        };
+        api.set_timeout(Some(CLIENT_TIMEOUT))?;
+        Ok(api)
+    }
+
+    /// Collects and encodes the metrics.
+    pub fn metrics(&mut self) -> String {
+        if self.api.is_none() {
+            match self.connect_api() {
+                Ok(api) => self.api = Some(api),
+                Err(_) => return down(),
+            }
+        }
+
+        match Metrics::collect(self.api.as_mut().unwrap()) {
+            Ok(metrics) => metrics.encode(),
+            Err(e) => {
+                // API reconnects after Disconnected, but the reconnect may have failed
+                if let Error::Timeout | Error::Io(_) | Error::Disconnected = e {
+                    self.api = None;
+                }
+                down()
+            }
+        }
+    }
+
+    /// Handles requests until accepting a connection fails. Requests are handled one at a time.
+    pub fn serve(&mut self, listener: &net::TcpListener) -> Result<()> {
+        for stream in listener.incoming() {
+            // Errors from one request should not stop the server
+            let _ = self.handle(stream?);
+        }
+        Ok(())
+    }
+
+    /// Reads one HTTP request and writes the response.
+    pub fn handle(&mut self, stream: net::TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(core::time::Duration::from_secs(10)))?;
+        let mut reader = std::io::BufReader::new(&stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+
+        // Skip headers
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+                break;
+            }
+        }
+
+        let mut words = request_line.split_whitespace();
+        let (status, content_type, body) = match (words.next(), words.next()) {
+            (Some("GET"), Some("/metrics")) => (
+                "200 OK",
+                "text/plain; version=0.0.4; charset=utf-8",
+                self.metrics(),
+            ),
+            (Some("GET"), Some("/")) => (
+                "200 OK",
+                "text/html; charset=utf-8",
+                "<a href=\"/metrics\">Metrics</a>\n".to_string(),
+            ),
+            (Some("GET"), _) => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+            _ => (
+                "405 Method Not Allowed",
+                "text/plain",
+                "Method not allowed\n".to_string(),
+            ),
+        };
+
+        write!(
+            &stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        )?;
+        Ok(())
+    }
+}
+
+/// Returns the metrics of a client that did not respond.
+fn down() -> String {
+    let mut out = String::new();
+    write_gauge(&mut out, "fah_up", UP_HELP, [(String::new(), 0.0)]);
+    out
+}
+
+/// Writes the HELP and TYPE lines and each sample. Nothing is written if there are no samples.
+fn write_gauge(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    samples: impl IntoIterator<Item = (String, f64)>,
+) {
+    let mut samples = samples.into_iter().peekable();
+    if samples.peek().is_none() {
+        return;
+    }
+
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    for (labels, value) in samples {
+        let _ = writeln!(out, "{}{} {}", name, labels, value);
+    }
+}
+
+fn unit_labels(unit: &SlotQueueInfo) -> String {
+    labels(&[
+        ("slot", &unit.slot.to_string()),
+        ("project", &unit.project.to_string()),
+        ("run", &unit.run.to_string()),
+        ("clone", &unit.clone.to_string()),
+        ("gen", &unit.gen.to_string()),
+    ])
+}
+
+/// Formats labels like `{slot="00"}` with escaped values.
+fn labels(pairs: &[(&str, &str)]) -> String {
+    let pairs: Vec<String> = pairs
+        .iter()
+        .map(|(k, v)| {
+            let v = v
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", k, v)
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_labels() {
+        struct Test {
+            pairs: &'static [(&'static str, &'static str)],
+            expected: &'static str,
+        }
+
+        let tests = [
+            Test {
+                pairs: &[],
+                expected: "{}",
+            },
+            Test {
+                pairs: &[("slot", "00"), ("project", "1")],
+                expected: "{slot=\"00\",project=\"1\"}",
+            },
+            Test {
+                pairs: &[("a", "\"\\\n")],
+                expected: "{a=\"\\\"\\\\\\n\"}",
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            assert_eq!(labels(test.pairs), test.expected, "{}", i);
+        }
+    }
+
+    #[test]
+    fn test_exporter() {
+        use std::io::Read;
+
+        let server = testing::MockServer::start().unwrap();
+        server.state().slots[0].status = SlotStatus::Paused;
+        let api = API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+        let mut exporter = Exporter::new(api);
+
+        let expected = r#"# HELP fah_up Whether the FAH client responded
+# TYPE fah_up gauge
+fah_up 1
+# HELP fah_ppd Estimated points per day
+# TYPE fah_ppd gauge
+fah_ppd 248734
+# HELP fah_uptime_seconds Time since the FAH client started
+# TYPE fah_uptime_seconds gauge
+fah_uptime_seconds 3723
+# HELP fah_slot_paused Whether the slot is paused
+# TYPE fah_slot_paused gauge
+fah_slot_paused{slot="00"} 1
+# HELP fah_wu_percent_done Progress of the work unit in percent
+# TYPE fah_wu_percent_done gauge
+fah_wu_percent_done{slot="00",project="13424",run="84",clone="7",gen="37"} 42.17
+# HELP fah_wu_eta_seconds Estimated time until the work unit is finished
+# TYPE fah_wu_eta_seconds gauge
+fah_wu_eta_seconds{slot="00",project="13424",run="84",clone="7",gen="37"} 7980
+# HELP fah_wu_credit_estimate Estimated credit for the work unit
+# TYPE fah_wu_credit_estimate gauge
+fah_wu_credit_estimate{slot="00",project="13424",run="84",clone="7",gen="37"} 27510
+# HELP fah_wu_attempts Number of attempts to download or upload the work unit
+# TYPE fah_wu_attempts gauge
+fah_wu_attempts{slot="00",project="13424",run="84",clone="7",gen="37"} 0
+"#;
+        assert_eq!(exporter.metrics(), expected);
+
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut request = |request: &str| -> String {
+            let mut client = net::TcpStream::connect(addr).unwrap();
+            client.write_all(request.as_bytes()).unwrap();
+            let (stream, _) = listener.accept().unwrap();
+            exporter.handle(stream).unwrap();
+            let mut response = String::new();
+            client.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let response = request("GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.ends_with(expected));
+        let response = request("GET /a HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        let response = request("POST /metrics HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+
+        // The client stopped responding
+        let addr = server.addr();
+        drop(server);
+        let down =
+            "# HELP fah_up Whether the FAH client responded\n# TYPE fah_up gauge\nfah_up 0\n";
+        assert_eq!(exporter.metrics(), down);
+        assert_eq!(exporter.metrics(), down);
+
+        // The client was restarted
+        let server = testing::MockServer::start_on(addr, testing::MockState::default()).unwrap();
+        server.state().slots[0].status = SlotStatus::Paused;
+        assert_eq!(exporter.metrics(), expected);
+
+        // The client is down when the exporter starts
+        drop(server);
+        let mut exporter = Exporter::connect(&addr, None, core::time::Duration::from_secs(1));
+        assert!(exporter.api.is_none());
+        assert_eq!(exporter.metrics(), down);
+        let server = testing::MockServer::start_on(addr, testing::MockState::default()).unwrap();
+        server.state().slots[0].status = SlotStatus::Paused;
+        assert_eq!(exporter.metrics(), expected);
+    }
+}
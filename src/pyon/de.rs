@@ -0,0 +1,393 @@
+use super::*;
+use serde::de::{self, IntoDeserializer};
+use std::borrow::Cow;
+
+/// Deserializes a single PyON value without a header or footer.
+///
+/// Example
+/// ```
+/// let list: Vec<Option<bool>> = fahapi::pyon::from_str("[True, None]").unwrap();
+/// assert_eq!(list, vec![Some(true), None]);
+/// ```
+pub fn from_str<'de, T>(s: &'de str) -> Result<T>
+where
+    T: de::Deserialize<'de>,
+{
+    let mut deserializer = Deserializer::from_str(s);
+    let result = T::deserialize(&mut deserializer)?;
+    deserializer.parser.end()?;
+    Ok(result)
+}
+
+/// Deserializes a complete PyON message including its header and footer. An empty message body
+/// is deserialized as `None`.
+pub fn from_message<'de, T>(s: &'de str) -> Result<T>
+where
+    T: de::Deserialize<'de>,
+{
+    let (start, end) = message_body(s)?;
+    if s[start..end].trim().is_empty() {
+        return T::deserialize(().into_deserializer());
+    }
+
+    let mut deserializer = Deserializer {
+        parser: Parser::with_range(s, start, end),
+    };
+    let result = T::deserialize(&mut deserializer)?;
+    deserializer.parser.end()?;
+    Ok(result)
+}
+
+/// Reads PyON directly into types that implement `serde::Deserialize`.
+///
+/// `None` is read as an empty string where a string is expected, because the FAH client reports
+/// unset string options as `None`.
+pub struct Deserializer<'de> {
+    parser: Parser<'de>,
+}
+
+impl<'de> Deserializer<'de> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &'de str) -> Self {
+        Self {
+            parser: Parser::new(s),
+        }
+    }
+
+    fn parse_str(&mut self) -> Result<Cow<'de, str>> {
+        self.parser.parse_string()
+    }
+
+    /// Returns true and consumes `None` if it is next.
+    fn eat_none(&mut self) -> Result<bool> {
+        if self.parser.peek() == Some(b'N') {
+            self.parser.parse_ident()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.parser.peek() {
+            Some(b'"' | b'\'') => match self.parse_str()? {
+                Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+                Cow::Owned(s) => visitor.visit_string(s),
+            },
+            Some(b'0'..=b'9' | b'-' | b'+' | b'.') => {
+                let start = self.parser.pos;
+                let s = self.parser.parse_number()?;
+                let result = if s.contains(&['.', 'e', 'E'] as &[char]) {
+                    s.parse::<f64>().map(|n| visitor.visit_f64(n)).ok()
+                } else if let Ok(n) = s.parse::<i64>() {
+                    Some(visitor.visit_i64(n))
+                } else if let Ok(n) = s.parse::<u64>() {
+                    Some(visitor.visit_u64(n))
+                } else {
+                    s.parse::<f64>().map(|n| visitor.visit_f64(n)).ok()
+                };
+                match result {
+                    Some(result) => result,
+                    None => {
+                        self.parser.pos = start;
+                        Err(self.parser.error("invalid number"))
+                    }
+                }
+            }
+            Some(open @ (b'[' | b'(')) => {
+                self.parser.pos += 1;
+                let close = if open == b'[' { b']' } else { b')' };
+                let mut access = SeqAccess {
+                    de: self,
+                    close,
+                    first: true,
+                    done: false,
+                };
+                let result = visitor.visit_seq(&mut access)?;
+                access.end()?;
+                Ok(result)
+            }
+            Some(b'{') => {
+                self.parser.pos += 1;
+                let mut access = MapAccess {
+                    de: self,
+                    first: true,
+                    done: false,
+                };
+                let result = visitor.visit_map(&mut access)?;
+                access.end()?;
+                Ok(result)
+            }
+            Some(_) => match self.parser.parse_ident()? {
+                "True" => visitor.visit_bool(true),
+                "False" => visitor.visit_bool(false),
+                _ => visitor.visit_unit(),
+            },
+            None => Err(self.parser.error("unexpected end of input")),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.eat_none()? {
+            return visitor.visit_borrowed_str("");
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.eat_none()? {
+            return visitor.visit_none();
+        }
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.parser.peek() {
+            // Unit variant
+            Some(b'"' | b'\'') => visitor.visit_enum(self.parse_str()?.into_deserializer()),
+            // {"Variant": value}
+            Some(b'{') => {
+                self.parser.pos += 1;
+                let result = visitor.visit_enum(EnumAccess { de: self })?;
+                self.parser.expect(b'}')?;
+                Ok(result)
+            }
+            _ => Err(self.parser.error("expected enum")),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    close: u8,
+    first: bool,
+    done: bool,
+}
+
+impl SeqAccess<'_, '_> {
+    /// Returns an error if the visitor did not read all elements.
+    fn end(&mut self) -> Result<()> {
+        if !self.done && !self.de.parser.next_element(self.close, self.first)? {
+            return Err(self.de.parser.error("too many elements"));
+        }
+        Ok(())
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.de.parser.next_element(self.close, self.first)? {
+            self.done = true;
+            return Ok(None);
+        }
+        self.first = false;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+struct MapAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    first: bool,
+    done: bool,
+}
+
+impl MapAccess<'_, '_> {
+    /// Returns an error if the visitor did not read all entries.
+    fn end(&mut self) -> Result<()> {
+        if !self.done && !self.de.parser.next_element(b'}', self.first)? {
+            return Err(self.de.parser.error("too many entries"));
+        }
+        Ok(())
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        if self.de.parser.next_element(b'}', self.first)? {
+            self.done = true;
+            return Ok(None);
+        }
+        self.first = false;
+
+        match self.de.parser.peek() {
+            Some(b'"' | b'\'') => seed.deserialize(&mut *self.de).map(Some),
+            // Numeric keys are read as strings
+            _ => {
+                let key = self.de.parser.parse_number()?;
+                seed.deserialize(de::value::BorrowedStrDeserializer::new(key))
+                    .map(Some)
+            }
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        self.de.parser.expect(b':')?;
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+struct EnumAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'_, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(&mut *self.de)?;
+        self.de.parser.expect(b':')?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for EnumAccess<'_, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        de::Deserialize::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self.de, visitor)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self.de, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct Test {
+            name: String,
+            unset: String,
+            count: i64,
+            ratio: f64,
+            flags: Vec<bool>,
+            pair: (i64, String),
+            maybe: Option<i64>,
+            kind: Kind,
+        }
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        enum Kind {
+            A,
+            B(i64),
+        }
+
+        let s = r#"{
+            "name": "a\"b",
+            'unset': None,
+            "count": -3,
+            "ratio": 1,
+            "flags": [True, False,],
+            "pair": (1, 'x'),
+            "maybe": None,
+            "kind": "A",
+        }"#;
+        let expected = Test {
+            name: "a\"b".into(),
+            unset: "".into(),
+            count: -3,
+            ratio: 1.0,
+            flags: vec![true, false],
+            pair: (1, "x".into()),
+            maybe: None,
+            kind: Kind::A,
+        };
+        assert_eq!(from_str::<Test>(s).unwrap(), expected);
+
+        assert_eq!(from_str::<Kind>(r#"{"B": 2}"#).unwrap(), Kind::B(2));
+        assert_eq!(from_str::<Option<i64>>("1").unwrap(), Some(1));
+        assert!(from_str::<i64>("1 2").is_err());
+        assert!(from_str::<i64>(r#""1""#).is_err());
+        assert!(from_str::<Test>("{").is_err());
+    }
+
+    #[test]
+    fn test_from_message() {
+        let result: Vec<i64> = from_message("PyON 1 units\n[1, 2]\n---").unwrap();
+        assert_eq!(result, vec![1, 2]);
+
+        let result: Option<i64> = from_message("PyON 1 units\n\n---").unwrap();
+        assert_eq!(result, None);
+
+        assert!(from_message::<Vec<i64>>("[1, 2]").is_err());
+    }
+}
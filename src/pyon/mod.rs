@@ -0,0 +1,368 @@
+//! Parser for PyON, the Python object notation that the FAH client uses in its responses.
+//!
+//! A PyON message looks like this:
+//! ```text
+//! PyON 1 units
+//! [{"id": "00", "attempts": 0, "error": None}]
+//! ---
+//! ```
+//! The supported subset is the one the FAH client emits: `None`, `True`, `False`, numbers, single
+//! or double quoted strings, lists, tuples and dicts. Use [`from_str()`](./fn.from_str.html) or
+//! [`from_message()`](./fn.from_message.html) to read PyON and [`to_string()`](./fn.to_string.html)
+//! to write it.
+
+mod de;
+mod ser;
+
+pub use de::*;
+pub use ser::*;
+
+use crate::{Error, Result};
+use std::borrow::Cow;
+
+/// Parses a complete PyON message including its header and footer. An empty message body is
+/// parsed as null.
+pub fn parse_message(s: &str) -> Result<serde_json::Value> {
+    from_message(s)
+}
+
+/// Parses a single PyON value without a header or footer.
+pub fn parse(s: &str) -> Result<serde_json::Value> {
+    from_str(s)
+}
+
+/// Returns the name in the header of a PyON message, such as `units` in `PyON 1 units`.
+pub fn message_name(s: &str) -> Result<&str> {
+    message_body(s)?;
+    let header = s.lines().next().unwrap_or_default();
+    match header.split(' ').nth(2) {
+        Some(name) if !name.is_empty() => Ok(name),
+        _ => Err(Error::PyonSyntax {
+            input: s.to_string(),
+            position: 0,
+            message: "expected message name".to_string(),
+        }),
+    }
+}
+
+/// Returns the byte range of the body of a PyON message, which is between the header line and
+/// the `\n---` footer.
+pub(crate) fn message_body(s: &str) -> Result<(usize, usize)> {
+    // https://pypi.org/project/pon/
+    const PREFIX: &str = "PyON";
+    const SUFFIX: &str = "\n---";
+    if !s.starts_with(PREFIX) || !s.ends_with(SUFFIX) {
+        return Err(Error::PyonSyntax {
+            input: s.to_string(),
+            position: 0,
+            message: "expected PyON header and footer".to_string(),
+        });
+    }
+
+    let end = s.len() - SUFFIX.len();
+    let start = match s.find('\n') {
+        Some(i) => i + 1,
+        None => 0,
+    };
+    Ok((start.min(end), end))
+}
+
+/// Tokenizer and recursive descent parser for PyON values.
+pub(crate) struct Parser<'a> {
+    input: &'a str,
+    pub(crate) pos: usize,
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self::with_range(input, 0, input.len())
+    }
+
+    /// Only parses input[start..end]. Error positions are relative to the whole input.
+    pub(crate) fn with_range(input: &'a str, start: usize, end: usize) -> Self {
+        Self {
+            input,
+            pos: start,
+            end,
+        }
+    }
+
+    pub(crate) fn error(&self, message: impl Into<String>) -> Error {
+        Error::PyonSyntax {
+            input: self.input.to_string(),
+            position: self.pos,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.input.as_bytes()[..self.end].get(self.pos) {
+            if !b.is_ascii_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Returns the next non-whitespace byte without consuming it.
+    pub(crate) fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.input.as_bytes()[..self.end].get(self.pos).copied()
+    }
+
+    /// Consumes the next non-whitespace byte if it is `b`.
+    pub(crate) fn eat(&mut self, b: u8) -> bool {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn expect(&mut self, b: u8) -> Result<()> {
+        if self.eat(b) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", b as char)))
+        }
+    }
+
+    /// Returns an error if there is anything but whitespace left.
+    pub(crate) fn end(&mut self) -> Result<()> {
+        match self.peek() {
+            Some(_) => Err(self.error("trailing characters")),
+            None => Ok(()),
+        }
+    }
+
+    /// Consumes a separator between elements of a list or dict and returns true if the closing
+    /// bracket was reached. Trailing commas are allowed.
+    pub(crate) fn next_element(&mut self, close: u8, first: bool) -> Result<bool> {
+        if self.eat(close) {
+            return Ok(true);
+        }
+        if !first {
+            self.expect(b',')?;
+            if self.eat(close) {
+                return Ok(true);
+            }
+        }
+        if self.peek().is_none() {
+            return Err(self.error(format!("expected '{}'", close as char)));
+        }
+        Ok(false)
+    }
+
+    /// Parses `None`, `True` or `False`.
+    pub(crate) fn parse_ident(&mut self) -> Result<&'a str> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let bytes = &self.input.as_bytes()[..self.end];
+        while self.pos < bytes.len() && bytes[self.pos].is_ascii_alphabetic() {
+            self.pos += 1;
+        }
+        match &self.input[start..self.pos] {
+            s @ ("None" | "True" | "False") => Ok(s),
+            _ => {
+                self.pos = start;
+                Err(self.error("expected value"))
+            }
+        }
+    }
+
+    /// Returns the text of the next number.
+    pub(crate) fn parse_number(&mut self) -> Result<&'a str> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let bytes = &self.input.as_bytes()[..self.end];
+        while self.pos < bytes.len()
+            && matches!(
+                bytes[self.pos],
+                b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'
+            )
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error("expected number"));
+        }
+        Ok(&self.input[start..self.pos])
+    }
+
+    /// Parses a single or double quoted string and unescapes it.
+    pub(crate) fn parse_string(&mut self) -> Result<Cow<'a, str>> {
+        let quote = match self.peek() {
+            Some(b @ (b'"' | b'\'')) => b,
+            _ => return Err(self.error("expected string")),
+        };
+        self.pos += 1;
+        let start = self.pos;
+
+        let bytes = &self.input.as_bytes()[..self.end];
+        let mut owned: Option<String> = None;
+        let mut chunk_start = start;
+        loop {
+            let b = match bytes.get(self.pos) {
+                Some(b) => *b,
+                None => return Err(self.error("unterminated string")),
+            };
+
+            if b == quote {
+                let s = &self.input[chunk_start..self.pos];
+                self.pos += 1;
+                return Ok(match owned {
+                    Some(mut owned) => {
+                        owned.push_str(s);
+                        Cow::Owned(owned)
+                    }
+                    None => Cow::Borrowed(s),
+                });
+            }
+
+            if b != b'\\' {
+                self.pos += 1;
+                continue;
+            }
+
+            let owned = owned.get_or_insert_with(String::new);
+            owned.push_str(&self.input[chunk_start..self.pos]);
+            let escape_start = self.pos;
+            self.pos += 1;
+            let escaped = match bytes.get(self.pos) {
+                Some(b) => *b,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.pos += 1;
+            match escaped {
+                b'n' => owned.push('\n'),
+                b'r' => owned.push('\r'),
+                b't' => owned.push('\t'),
+                b'0' => owned.push('\0'),
+                b'"' => owned.push('"'),
+                b'\'' => owned.push('\''),
+                b'\\' => owned.push('\\'),
+                b'x' | b'u' => {
+                    let len = if escaped == b'x' { 2 } else { 4 };
+                    let c = self
+                        .input
+                        .get(self.pos..self.pos + len)
+                        .filter(|_| self.pos + len <= self.end)
+                        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                        .and_then(std::char::from_u32);
+                    match c {
+                        Some(c) => {
+                            owned.push(c);
+                            self.pos += len;
+                        }
+                        // Keep invalid escape sequences as they are
+                        None => owned.push_str(&self.input[escape_start..self.pos]),
+                    }
+                }
+                _ => {
+                    // Keep unknown escape sequences as they are. Back up in case the escaped
+                    // character is not a single byte.
+                    self.pos -= 1;
+                    owned.push('\\');
+                }
+            }
+            chunk_start = self.pos;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        struct Test {
+            s: &'static str,
+            expected: serde_json::Value,
+            expect_error: bool,
+        }
+
+        let tests = [
+            Test {
+                s: "",
+                expected: serde_json::Value::Null,
+                expect_error: true,
+            },
+            Test {
+                s: "None",
+                expected: serde_json::Value::Null,
+                expect_error: false,
+            },
+            Test {
+                s: "[True, False, None]",
+                expected: serde_json::json!([true, false, null]),
+                expect_error: false,
+            },
+            Test {
+                s: r#"{"a": "None", 'b': 'it\'s', "c": (1, -2.5,)}"#,
+                expected: serde_json::json!({"a": "None", "b": "it's", "c": [1, -2.5]}),
+                expect_error: false,
+            },
+            Test {
+                s: r#""\n\"\\\x01é\q""#,
+                expected: serde_json::json!("\n\"\\\x01é\\q"),
+                expect_error: false,
+            },
+            Test {
+                s: r#""a"#,
+                expected: serde_json::Value::Null,
+                expect_error: true,
+            },
+            Test {
+                s: "[1 2]",
+                expected: serde_json::Value::Null,
+                expect_error: true,
+            },
+            Test {
+                s: "{}",
+                expected: serde_json::json!({}),
+                expect_error: false,
+            },
+            Test {
+                s: "Nonesuch",
+                expected: serde_json::Value::Null,
+                expect_error: true,
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let result = parse(test.s);
+            assert_eq!(result.is_err(), test.expect_error, "{}", i);
+            if !test.expect_error {
+                assert_eq!(result.unwrap(), test.expected, "{}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_error_position() {
+        match parse_message("PyON 1 units\n[1, x]\n---") {
+            Err(Error::PyonSyntax { position, .. }) => assert_eq!(position, 17),
+            result => panic!("{:?}", result),
+        }
+
+        match parse_message("PyON 1 units\n[1, 2\n---") {
+            Err(Error::PyonSyntax { position, .. }) => assert_eq!(position, 18),
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_message_name() {
+        assert_eq!(message_name("PyON 1 units\n[]\n---").unwrap(), "units");
+        assert_eq!(
+            message_name("PyON 1 log-update\n\n---").unwrap(),
+            "log-update"
+        );
+        assert!(message_name("PyON 1\n[]\n---").is_err());
+        assert!(message_name("PyON 1 units\n[]").is_err());
+    }
+}
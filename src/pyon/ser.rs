@@ -0,0 +1,475 @@
+use crate::{Error, Result};
+use serde::ser::{self, Serialize};
+
+/// Serializes a value to PyON.
+///
+/// Example
+/// ```
+/// let s = fahapi::pyon::to_string(&vec![Some("a \"b\""), None]).unwrap();
+/// assert_eq!(s, r#"["a \"b\"", None]"#);
+/// ```
+pub fn to_string<T>(value: &T) -> Result<String>
+where
+    T: Serialize + ?Sized,
+{
+    let mut serializer = Serializer {
+        output: String::new(),
+    };
+    value.serialize(&mut serializer)?;
+    Ok(serializer.output)
+}
+
+/// Returns a command argument that the FAH client reads as `s`. The argument is quoted and
+/// escaped if it contains anything other than letters, digits and `-_.,:/=+@`.
+pub fn to_command_arg(s: &str) -> Result<String> {
+    let is_plain = |c: char| c.is_ascii_alphanumeric() || "-_.,:/=+@".contains(c);
+    if !s.is_empty() && s.chars().all(is_plain) {
+        Ok(s.to_string())
+    } else {
+        to_string(s)
+    }
+}
+
+/// Writes PyON that can be read by the FAH client and by Python's `ast.literal_eval()`.
+pub struct Serializer {
+    output: String,
+}
+
+impl Serializer {
+    fn write_str(&mut self, s: &str) {
+        self.output.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => self.output.push_str("\\\""),
+                '\\' => self.output.push_str("\\\\"),
+                '\n' => self.output.push_str("\\n"),
+                '\r' => self.output.push_str("\\r"),
+                '\t' => self.output.push_str("\\t"),
+                // $ starts a substitution in the FAH command line
+                '$' => self.output.push_str("\\x24"),
+                c if (c as u32) < 0x20 || c as u32 == 0x7f => {
+                    self.output.push_str(&format!("\\x{:02x}", c as u32))
+                }
+                c => self.output.push(c),
+            }
+        }
+        self.output.push('"');
+    }
+
+    fn compound(&mut self, open: char, close: &'static str) -> Compound<'_> {
+        self.output.push(open);
+        Compound {
+            ser: self,
+            len: 0,
+            close,
+        }
+    }
+
+    /// Starts {"variant": ...
+    fn variant(&mut self, variant: &str) {
+        self.output.push('{');
+        self.write_str(variant);
+        self.output.push_str(": ");
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.output.push_str(if v { "True" } else { "False" });
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.output.push_str(&v.to_string());
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.output.push_str(&v.to_string());
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        if !v.is_finite() {
+            return Err(ser::Error::custom(format!("cannot serialize {}", v)));
+        }
+        // Debug keeps the decimal point like Python's repr()
+        self.output.push_str(&format!("{:?}", v));
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.write_str(v.encode_utf8(&mut [0; 4]));
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.write_str(v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        use ser::SerializeSeq;
+        let mut seq = self.serialize_seq(Some(v.len()))?;
+        for b in v {
+            seq.serialize_element(b)?;
+        }
+        seq.end()
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        self.output.push_str("None");
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.variant(variant);
+        value.serialize(&mut *self)?;
+        self.output.push('}');
+        Ok(())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>> {
+        Ok(self.compound('[', "]"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>> {
+        Ok(self.compound('(', ")"))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>> {
+        Ok(self.compound('(', ")"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>> {
+        self.variant(variant);
+        Ok(self.compound('(', ")}"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>> {
+        Ok(self.compound('{', "}"))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>> {
+        Ok(self.compound('{', "}"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>> {
+        self.variant(variant);
+        Ok(self.compound('{', "}}"))
+    }
+}
+
+/// Serializes lists, tuples and dicts.
+pub struct Compound<'a> {
+    ser: &'a mut Serializer,
+    len: usize,
+    close: &'static str,
+}
+
+impl Compound<'_> {
+    fn separator(&mut self) {
+        if self.len > 0 {
+            self.ser.output.push_str(", ");
+        }
+        self.len += 1;
+    }
+
+    fn element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.separator();
+        value.serialize(&mut *self.ser)
+    }
+
+    fn entry<T>(&mut self, key: &str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.separator();
+        self.ser.write_str(key);
+        self.ser.output.push_str(": ");
+        value.serialize(&mut *self.ser)
+    }
+
+    fn finish(self) -> Result<()> {
+        // A tuple with one element needs a trailing comma
+        if self.close.starts_with(')') && self.len == 1 {
+            self.ser.output.push(',');
+        }
+        self.ser.output.push_str(self.close);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.separator();
+        key.serialize(&mut *self.ser)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.ser.output.push_str(": ");
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.entry(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.entry(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_to_string() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        #[serde(rename_all = "kebab-case")]
+        struct Test {
+            name: String,
+            extra_core_args: Vec<String>,
+            ratio: f64,
+            maybe: Option<i64>,
+            pair: (bool, char),
+            single: (i64,),
+            kind: Kind,
+        }
+
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        enum Kind {
+            A,
+            B(i64),
+        }
+
+        let value = Test {
+            name: "a \"b\"\n$(c)".into(),
+            extra_core_args: vec!["-a".into(), "-b 1".into()],
+            ratio: 1.0,
+            maybe: None,
+            pair: (true, 'x'),
+            single: (1,),
+            kind: Kind::B(2),
+        };
+        let s = to_string(&value).unwrap();
+        assert_eq!(
+            s,
+            r#"{"name": "a \"b\"\n\x24(c)", "extra-core-args": ["-a", "-b 1"], "ratio": 1.0, "maybe": None, "pair": (True, "x"), "single": (1,), "kind": {"B": 2}}"#
+        );
+        assert_eq!(crate::pyon::from_str::<Test>(&s).unwrap(), value);
+
+        assert_eq!(to_string(&Kind::A).unwrap(), r#""A""#);
+        assert_eq!(to_string(&()).unwrap(), "None");
+        assert!(to_string(&f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_to_command_arg() {
+        assert_eq!(to_command_arg("FULL").unwrap(), "FULL");
+        assert_eq!(to_command_arg("power=FULL").unwrap(), "power=FULL");
+        assert_eq!(to_command_arg("").unwrap(), r#""""#);
+        assert_eq!(
+            to_command_arg("extra-core-args=-a -b").unwrap(),
+            r#""extra-core-args=-a -b""#
+        );
+        assert_eq!(to_command_arg("a\"\n").unwrap(), r#""a\"\n""#);
+    }
+}
@@ -0,0 +1,215 @@
+use super::*;
+use std::collections::BTreeMap;
+
+/// Version of the snapshot file format. Snapshots with a newer version cannot be read.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// All options of a FAH client and of each of its slots, as returned by `options -a` and
+/// `slot-options <slot> -a`. Options without a value are None.
+///
+/// Example
+/// ```no_run
+/// let mut api = fahapi::API::connect_timeout(
+///     &fahapi::DEFAULT_ADDR,
+///     std::time::Duration::from_secs(1),
+/// ).unwrap();
+/// let snapshot = fahapi::Snapshot::take(&mut api, "localhost").unwrap();
+/// snapshot.save("fah-options.json").unwrap();
+///
+/// api.options_set("power", fahapi::Power::PowerFull).unwrap();
+///
+/// let snapshot = fahapi::Snapshot::load("fah-options.json").unwrap();
+/// for warning in snapshot.restore(&mut api).unwrap() {
+///     eprintln!("{}", warning);
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Snapshot {
+    pub version: u32,
+    /// Host that the snapshot was taken from
+    pub host: String,
+    pub time: FAHTime,
+    pub client_version: String,
+    pub options: BTreeMap<String, Option<String>>,
+    pub slots: BTreeMap<SlotId, BTreeMap<String, Option<String>>>,
+}
+
+impl Snapshot {
+    /// Gets all options from the client.
+    pub fn take(api: &mut API, host: &str) -> Result<Self> {
+        let mut slots = BTreeMap::new();
+        for slot in api.slot_info()? {
+            slots.insert(slot.id, slot_options(api, slot.id)?);
+        }
+
+        Ok(Self {
+            version: SNAPSHOT_VERSION,
+            host: host.to_string(),
+            time: chrono::Utc::now().into(),
+            client_version: api.info_struct()?.fah_client.version,
+            options: options(api)?,
+            slots,
+        })
+    }
+
+    pub fn from_json(s: &str) -> Result<Self> {
+        let snapshot: Self = serde_json::from_str(s).map_err(|e| Error::Other(e.into()))?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(Error::UnexpectedShape(format!(
+                "snapshot version {} is newer than {}",
+                snapshot.version, SNAPSHOT_VERSION
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        Ok(std::fs::write(path, self.to_json())?)
+    }
+
+    /// Sets the options that differ from the snapshot, and returns warnings about what could
+    /// not be restored exactly: a different client version, and slots that no longer exist.
+    /// Options without a value in the snapshot are not changed.
+    ///
+    /// If setting an option fails, the options that were already set are set back to their old
+    /// values, and the error is returned.
+    pub fn restore(&self, api: &mut API) -> Result<Vec<String>> {
+        let mut warnings = Vec::new();
+        let client_version = api.info_struct()?.fah_client.version;
+        if client_version != self.client_version {
+            warnings.push(format!(
+                "snapshot is from client version {} but the client is version {}",
+                self.client_version, client_version
+            ));
+        }
+
+        let mut changes = diff(None, &options(api)?, &self.options);
+        let current_slots: Vec<SlotId> = api.slot_info()?.iter().map(|slot| slot.id).collect();
+        for (slot, options) in &self.slots {
+            if !current_slots.contains(slot) {
+                warnings.push(format!("slot {} does not exist", slot));
+                continue;
+            }
+            changes.extend(diff(Some(*slot), &slot_options(api, *slot)?, options));
+        }
+
+        apply_changes(api, &changes)?;
+        Ok(warnings)
+    }
+}
+
+/// Returns the changes from `current` to the values in `snapshot`.
+fn diff(
+    slot: Option<SlotId>,
+    current: &BTreeMap<String, Option<String>>,
+    snapshot: &BTreeMap<String, Option<String>>,
+) -> Vec<Change> {
+    snapshot
+        .iter()
+        .filter_map(|(key, value)| {
+            let new = value.as_ref()?;
+            let old = current.get(key).cloned().flatten();
+            if old.as_ref() == Some(new) {
+                return None;
+            }
+            Some(Change {
+                slot,
+                key: key.clone(),
+                old,
+                new: new.clone(),
+            })
+        })
+        .collect()
+}
+
+fn options(api: &mut API) -> Result<BTreeMap<String, Option<String>>> {
+    api.conn.exec("options -a", &mut api.buf)?;
+    Ok(option_values(parse_pyon(&api.buf)?))
+}
+
+fn slot_options(api: &mut API, slot: SlotId) -> Result<BTreeMap<String, Option<String>>> {
+    let command = format!("slot-options {} -a", slot.0);
+    api.conn.exec(command.as_str(), &mut api.buf)?;
+    Ok(option_values(parse_pyon(&api.buf)?))
+}
+
+/// Converts an options dict to strings. Values other than strings and None are unexpected, but
+/// are kept as JSON.
+fn option_values(options: BTreeMap<String, serde_json::Value>) -> BTreeMap<String, Option<String>> {
+    options
+        .into_iter()
+        .map(|(k, v)| {
+            let v = match v {
+                serde_json::Value::Null => None,
+                serde_json::Value::String(s) => Some(s),
+                v => Some(v.to_string()),
+            };
+            (k, v)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_snapshot() {
+        let server = testing::MockServer::start().unwrap();
+        let mut api =
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+
+        let snapshot = Snapshot::take(&mut api, "rig1").unwrap();
+        assert_eq!(snapshot.version, SNAPSHOT_VERSION);
+        assert_eq!(snapshot.host, "rig1");
+        assert!(snapshot.time.0.is_some());
+        assert_eq!(snapshot.client_version, "7.6.21");
+        assert_eq!(snapshot.options["power"].as_deref(), Some("MEDIUM"));
+        assert_eq!(
+            snapshot.slots[&SlotId(0)]["paused"].as_deref(),
+            Some("false")
+        );
+        assert_eq!(Snapshot::from_json(&snapshot.to_json()).unwrap(), snapshot);
+
+        api.options_set("power", Power::PowerFull).unwrap();
+        api.options_set("cpu-usage", 50).unwrap();
+        api.slot_options_set(SlotId(0), "machine-id", 3).unwrap();
+        let commands = server.state().commands.len();
+
+        assert_eq!(snapshot.restore(&mut api).unwrap(), Vec::<String>::new());
+        assert_eq!(server.state().options["power"], "MEDIUM");
+        assert_eq!(server.state().options["cpu-usage"], "100");
+        assert_eq!(server.state().slots[0].options["machine-id"], "0");
+        // Only the options that differ are set
+        let sets = server.state().commands[commands..]
+            .iter()
+            .filter(|c| c.starts_with("options ") && !c.ends_with("-a"))
+            .count();
+        assert_eq!(sets, 2);
+
+        let mut old = snapshot.clone();
+        old.client_version = "7.5.1".to_string();
+        old.slots.insert(SlotId(1), BTreeMap::new());
+        assert_eq!(
+            old.restore(&mut api).unwrap(),
+            [
+                "snapshot is from client version 7.5.1 but the client is version 7.6.21",
+                "slot 01 does not exist",
+            ]
+        );
+
+        let mut newer = snapshot;
+        newer.version = SNAPSHOT_VERSION + 1;
+        assert!(Snapshot::from_json(&newer.to_json()).is_err());
+    }
+}
@@ -0,0 +1,880 @@
+//! In-process stand-in for the FAH client, for testing code that uses this crate without a running
+//! client. Enable the `testing` feature to use this module outside of this crate.
+
+use super::*;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Listens on a local port and answers the commands that [`API`](../struct.API.html) sends, like
+/// the FAH client's command server does. Responses are generated from [`MockState`], which tests
+/// can inspect and modify. For example, `pause` changes the status that `slot-info` returns.
+///
+/// The server stops when it is dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: net::SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    connections: Arc<Mutex<Vec<MockConnection>>>,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Starts a server on a random local port with the default state.
+    pub fn start() -> Result<Self> {
+        Self::start_with_state(MockState::default())
+    }
+
+    pub fn start_with_state(state: MockState) -> Result<Self> {
+        Self::start_on((net::Ipv4Addr::LOCALHOST, 0).into(), state)
+    }
+
+    /// Starts a server on the address. Use the address of a dropped server to simulate a client
+    /// restart.
+    pub fn start_on(addr: net::SocketAddr, state: MockState) -> Result<Self> {
+        let listener = net::TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(state));
+        let connections: Arc<Mutex<Vec<MockConnection>>> = Arc::new(Mutex::new(Vec::new()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let state = state.clone();
+            let connections = connections.clone();
+            let shutdown = shutdown.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(_) => continue,
+                    };
+                    if let Ok(clone) = stream.try_clone() {
+                        connections.lock().unwrap().push(MockConnection {
+                            stream: clone,
+                            log_updates: false,
+                            updates: BTreeMap::new(),
+                        });
+                    }
+                    let state = state.clone();
+                    let connections = connections.clone();
+                    std::thread::spawn(move || {
+                        // The connection is closed when this returns
+                        let _ = serve(stream, &state, &connections);
+                    });
+                }
+            })
+        };
+
+        Ok(Self {
+            addr,
+            state,
+            connections,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the address to connect to.
+    pub fn addr(&self) -> net::SocketAddr {
+        self.addr
+    }
+
+    /// Locks and returns the state that responses are generated from.
+    pub fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Appends text to the log and sends it to connections that started log updates.
+    pub fn append_log(&self, text: &str) -> Result<()> {
+        let mut state = self.state();
+        state.log.push_str(text);
+
+        let message = log_message("log-update", text)?;
+        for conn in self.connections.lock().unwrap().iter_mut() {
+            if conn.log_updates {
+                // Closed connections are ignored
+                let _ = conn.stream.write_all(message.as_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends the results of the expressions that connections registered with `updates add`, as
+    /// if their rate elapsed.
+    pub fn push_updates(&self) {
+        let mut state = self.state();
+        for conn in self.connections.lock().unwrap().iter_mut() {
+            for expression in conn.updates.values() {
+                if let Some(message) = state.evaluate(expression) {
+                    // Closed connections are ignored
+                    let _ = conn.stream.write_all(format!("{}\n", message).as_bytes());
+                }
+            }
+        }
+    }
+
+    /// Closes all open connections, as if the client was restarted.
+    pub fn disconnect(&self) {
+        for conn in self.connections.lock().unwrap().drain(..) {
+            let _ = conn.stream.shutdown(net::Shutdown::Both);
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        self.disconnect();
+        // Wake up the listener thread
+        let _ = net::TcpStream::connect(self.addr);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MockConnection {
+    stream: net::TcpStream,
+    log_updates: bool,
+    /// Expressions of `updates add` by ID
+    updates: BTreeMap<i64, String>,
+}
+
+/// Simulated state of the FAH client.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MockState {
+    pub slots: Vec<MockSlot>,
+    /// Client options by their kebab-case names. Empty values are sent as None.
+    pub options: BTreeMap<String, String>,
+    pub log: String,
+    pub ppd: f64,
+    /// Returned by `uptime` in humantime format.
+    pub uptime: String,
+    pub configured: bool,
+    /// If set, commands other than `auth` close the connection until the password is sent.
+    pub password: Option<String>,
+    /// Responses to exact command lines. These take precedence over generated responses.
+    pub responses: HashMap<String, String>,
+    /// Every command line that was received, in order.
+    pub commands: Vec<String>,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        let options = [
+            ("allow", "127.0.0.1"),
+            ("cause", "ANY"),
+            ("client-type", "normal"),
+            ("command-allow-no-pass", "127.0.0.1"),
+            ("cpu-usage", "100"),
+            ("cpus", "-1"),
+            ("extra-core-args", ""),
+            ("gpu", "false"),
+            ("gpu-usage", "100"),
+            ("log", "log.txt"),
+            ("machine-id", "0"),
+            ("passkey", ""),
+            ("password", ""),
+            ("power", "MEDIUM"),
+            ("proxy", ":8080"),
+            ("proxy-enable", "false"),
+            ("team", "0"),
+            ("user", "Anonymous"),
+        ];
+
+        Self {
+            slots: vec![MockSlot::default()],
+            options: options
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            log:
+                "*********************** Log Started 2020-04-20T09:13:27Z ***********************\n"
+                    .to_string(),
+            ppd: 248734.0,
+            uptime: "1h 2m 3s".to_string(),
+            configured: true,
+            password: None,
+            responses: HashMap::new(),
+            commands: Vec::new(),
+        }
+    }
+}
+
+/// Simulated folding slot.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MockSlot {
+    pub id: SlotId,
+    /// Running, Paused or Finishing
+    pub status: SlotStatus,
+    pub description: String,
+    /// Slot options by their kebab-case names.
+    pub options: BTreeMap<String, String>,
+}
+
+impl Default for MockSlot {
+    fn default() -> Self {
+        Self {
+            id: SlotId(0),
+            status: SlotStatus::Running,
+            description: "cpu:4".to_string(),
+            options: [("machine-id", "0"), ("paused", "false"), ("idle", "false")]
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl MockSlot {
+    /// Sets the description from the type, and sets options from `key=value` arguments.
+    fn modify(&mut self, slot_type: &str, options: &[String]) -> Option<()> {
+        for option in options {
+            let (k, v) = option.split_once('=')?;
+            self.options.insert(k.to_string(), v.to_string());
+        }
+        let index = match slot_type {
+            "cpu" => "cpus",
+            "gpu" => "gpu-index",
+            _ => return None,
+        };
+        let index = self.options.get(index).map_or("0", String::as_str);
+        self.description = format!("{}:{}", slot_type, index);
+        Some(())
+    }
+
+    fn set_status(&mut self, status: SlotStatus) {
+        self.options
+            .insert("paused".to_string(), status.is_paused().to_string());
+        self.status = status;
+    }
+}
+
+/// Answers commands on one connection.
+fn serve(
+    stream: net::TcpStream,
+    state: &Mutex<MockState>,
+    connections: &Mutex<Vec<MockConnection>>,
+) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+    let peer = stream.peer_addr()?;
+    writer.write_all(b"\nWelcome to the Folding@home Client command server.\n> ")?;
+
+    let mut authenticated = state.lock().unwrap().password.is_none();
+    for line in std::io::BufReader::new(stream).lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut state = state.lock().unwrap();
+        state.commands.push(line.to_string());
+
+        let response = match state.responses.get(line) {
+            Some(response) => response.clone(),
+            None => match state.respond(line, &mut authenticated) {
+                Some(response) => response,
+                // Invalid commands close the connection like the FAH client does. The server
+                // holds a clone of the stream, so it has to be shut down explicitly.
+                None => return Ok(writer.shutdown(net::Shutdown::Both)?),
+            },
+        };
+        writer.write_all(format!("\n{}\n> ", response).as_bytes())?;
+
+        if let Some(arg) = line.strip_prefix("log-updates ") {
+            let log_updates = arg != "stop";
+            if log_updates {
+                writer.write_all(log_message("log-restart", &state.log)?.as_bytes())?;
+            }
+            for conn in connections.lock().unwrap().iter_mut() {
+                if conn.stream.peer_addr().ok() == Some(peer) {
+                    conn.log_updates = log_updates;
+                }
+            }
+        }
+
+        if line.starts_with("updates ") {
+            let words = split_command(line).unwrap_or_default();
+            let mut connections = connections.lock().unwrap();
+            let conn = match connections
+                .iter_mut()
+                .find(|conn| conn.stream.peer_addr().ok() == Some(peer))
+            {
+                Some(conn) => conn,
+                None => continue,
+            };
+            let id: i64 = words
+                .get(2)
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_default();
+            match words.get(1).map(String::as_str) {
+                Some("add") if words.len() > 4 => {
+                    let expression = words[4..].join(" ");
+                    // The first result is sent immediately
+                    if let Some(message) = state.evaluate(&expression) {
+                        writer.write_all(format!("{}\n", message).as_bytes())?;
+                    }
+                    conn.updates.insert(id, expression);
+                }
+                Some("del") => {
+                    conn.updates.remove(&id);
+                }
+                _ => conn.updates.clear(),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns a pushed `log-restart` or `log-update` message.
+fn log_message(name: &str, log: &str) -> Result<String> {
+    Ok(format!("PyON 1 {}\n{}\n---\n", name, pyon::to_string(log)?))
+}
+
+fn pyon_message(name: &str, value: &serde_json::Value) -> Option<String> {
+    Some(format!(
+        "PyON 1 {}\n{}\n---",
+        name,
+        pyon::to_string(value).ok()?
+    ))
+}
+
+/// Splits a command line into words. Double quoted words are unescaped.
+fn split_command(line: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        if rest.starts_with('"') {
+            let mut parser = pyon::Parser::new(rest);
+            words.push(parser.parse_string().ok()?.into_owned());
+            rest = rest[parser.pos..].trim_start();
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            words.push(rest[..end].to_string());
+            rest = rest[end..].trim_start();
+        }
+    }
+    Some(words)
+}
+
+/// Converts options to a dict where empty values are None.
+fn options_value<'a>(options: impl Iterator<Item = (&'a String, &'a String)>) -> serde_json::Value {
+    serde_json::Value::Object(
+        options
+            .map(|(k, v)| {
+                let v = if v.is_empty() {
+                    serde_json::Value::Null
+                } else {
+                    v.clone().into()
+                };
+                (k.clone(), v)
+            })
+            .collect(),
+    )
+}
+
+impl MockState {
+    /// Returns the slot with the id in the argument.
+    fn slot_mut(&mut self, arg: Option<&String>) -> Option<&mut MockSlot> {
+        let id: SlotId = arg?.parse().ok()?;
+        self.slots.iter_mut().find(|slot| slot.id == id)
+    }
+
+    /// Sets the status of the slot in arg, or all slots if there is no argument.
+    fn set_status(&mut self, arg: Option<&String>, status: SlotStatus) -> Option<String> {
+        match arg {
+            Some(_) => self.slot_mut(arg)?.set_status(status),
+            None => self
+                .slots
+                .iter_mut()
+                .for_each(|slot| slot.set_status(status.clone())),
+        }
+        Some(String::new())
+    }
+
+    /// Returns the result of an `updates` expression such as `$queue-info` or `$(options -a)`.
+    fn evaluate(&mut self, expression: &str) -> Option<String> {
+        let command = expression.strip_prefix('$')?;
+        let command = command
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .unwrap_or(command);
+        self.respond(command, &mut true)
+    }
+
+    /// Returns the response to a command line or None if the command is invalid.
+    fn respond(&mut self, line: &str, authenticated: &mut bool) -> Option<String> {
+        use serde_json::json;
+
+        let words = split_command(line)?;
+        let (command, args) = words.split_first()?;
+
+        if command == "auth" {
+            if self.password.as_ref() == args.first() {
+                *authenticated = true;
+                return Some("OK".to_string());
+            }
+            return Some("FAILED".to_string());
+        }
+
+        if !*authenticated {
+            return None;
+        }
+
+        match command.as_str() {
+            "help" => Some("Folding@home Client\n  help   Print this help screen.".to_string()),
+            "eval" => {
+                // Only supports "$(command)\n"
+                let expr = args.first()?;
+                let inner = expr.strip_prefix("$(")?.strip_suffix(")\n")?;
+                let output = match inner {
+                    "uptime" => self.uptime.clone(),
+                    "date" => chrono::Utc::now().to_rfc2822(),
+                    "eval" => String::new(),
+                    _ => self.respond(inner, authenticated)?,
+                };
+                Some(output)
+            }
+            "updates" => match (args.first()?.as_str(), args.len()) {
+                ("add", n) if n >= 4 && args[1].parse::<i64>().is_ok() => Some(String::new()),
+                ("del", 2) if args[1].parse::<i64>().is_ok() => Some(String::new()),
+                ("clear", 1) => Some(String::new()),
+                _ => None,
+            },
+            "log-updates" => match args.first()?.as_str() {
+                "start" | "restart" | "stop" => Some(String::new()),
+                _ => None,
+            },
+            "screensaver" | "do-cycle" | "request-id" | "request-ws" | "shutdown"
+            | "wait-for-units" => Some(String::new()),
+            "configured" => pyon_message("configured", &json!(self.configured)),
+            "always_on" | "on_idle" => {
+                let idle = (command == "on_idle").to_string();
+                match args.first() {
+                    Some(_) => {
+                        self.slot_mut(args.first())?
+                            .options
+                            .insert("idle".to_string(), idle);
+                    }
+                    None => self.slots.iter_mut().for_each(|slot| {
+                        slot.options.insert("idle".to_string(), idle.clone());
+                    }),
+                }
+                Some(String::new())
+            }
+            "finish" => self.set_status(args.first(), SlotStatus::Finishing),
+            "pause" => self.set_status(args.first(), SlotStatus::Paused),
+            "unpause" => self.set_status(args.first(), SlotStatus::Running),
+            "info" => pyon_message(
+                "info",
+                &json!([
+                    [
+                        "FAHClient",
+                        ["Version", "7.6.21"],
+                        ["Homepage", "https://foldingathome.org/"]
+                    ],
+                    ["CBang", ["Date", "Oct 20 2020"]],
+                    [
+                        "System",
+                        ["CPU", "Intel(R) Core(TM) i5-4590 CPU @ 3.30GHz"],
+                        ["CPU ID", "GenuineIntel Family 6 Model 60 Stepping 3"],
+                        ["CPUs", "4"],
+                        ["Memory", "15.56GiB"],
+                        ["OS", "Linux 5.4.0-72-generic x86_64"],
+                        ["GPUs", "0"]
+                    ],
+                    ["libFAH", ["Date", "Oct 20 2020"]]
+                ]),
+            ),
+            "num-slots" => pyon_message("num-slots", &json!(self.slots.len())),
+            "options" => {
+                if args.first().map(String::as_str) == Some("-a") {
+                    return pyon_message("options", &options_value(self.options.iter()));
+                }
+                for arg in args {
+                    let (k, v) = arg.split_once('=')?;
+                    *self.options.get_mut(k)? = v.to_string();
+                }
+                Some(String::new())
+            }
+            "ppd" => pyon_message("ppd", &json!(self.ppd)),
+            "queue-info" => {
+                let units: Vec<serde_json::Value> = self
+                    .slots
+                    .iter()
+                    .map(|slot| {
+                        let state = if slot.status.is_paused() {
+                            "READY"
+                        } else {
+                            "RUNNING"
+                        };
+                        json!({
+                            "id": slot.id.to_string(),
+                            "state": state,
+                            "error": "NO_ERROR",
+                            "project": 13424,
+                            "run": 84,
+                            "clone": 7,
+                            "gen": 37,
+                            "core": "0xa7",
+                            "unit": "0x0000002a287234c95e8a93ff63ec35b1",
+                            "percent-done": "42.17%",
+                            "eta": "2 hours 13 mins",
+                            "ppd": "248734",
+                            "credit-estimate": "27510",
+                            "waiting-on": "",
+                            "next-attempt": "0.00 secs",
+                            "time-remaining": "1.91 days",
+                            "total-frames": 100,
+                            "frames-done": 42,
+                            "assigned": "2020-04-20T09:13:27Z",
+                            "timeout": "2020-04-21T09:13:27Z",
+                            "deadline": "2020-04-23T09:13:27Z",
+                            "ws": "155.247.166.219",
+                            "cs": "0.0.0.0",
+                            "attempts": 0,
+                            "slot": slot.id.to_string(),
+                            "tpf": "2 mins 38 secs",
+                            "base-credit": "9405"
+                        })
+                    })
+                    .collect();
+                pyon_message("units", &json!(units))
+            }
+            "simulation-info" => {
+                let slot = self.slot_mut(args.first())?;
+                pyon_message(
+                    "simulation-info",
+                    &json!({
+                        "user": "Anonymous",
+                        "team": "0",
+                        "project": 13424,
+                        "run": 84,
+                        "clone": 7,
+                        "gen": 37,
+                        "core-type": 167,
+                        "core": "GROMACS",
+                        "total-iterations": 250000,
+                        "iterations-done": 105000,
+                        "start-time": "2020-04-20T09:13:27Z",
+                        "timeout": 1587460407,
+                        "deadline": 1587633207,
+                        "eta": 8000,
+                        "progress": 0.42,
+                        "slot": slot.id.0
+                    }),
+                )
+            }
+            "slot-add" => {
+                let (slot_type, options) = args.split_first()?;
+                let id = self
+                    .slots
+                    .iter()
+                    .map(|slot| slot.id.0 + 1)
+                    .max()
+                    .unwrap_or(0);
+                let mut slot = MockSlot {
+                    id: SlotId(id),
+                    ..Default::default()
+                };
+                slot.modify(slot_type, options)?;
+                self.slots.push(slot);
+                Some(String::new())
+            }
+            "slot-modify" => {
+                let slot = self.slot_mut(args.first())?;
+                let (slot_type, options) = args.get(1..)?.split_first()?;
+                slot.modify(slot_type, options)?;
+                Some(String::new())
+            }
+            "slot-delete" => {
+                let id = self.slot_mut(args.first())?.id;
+                self.slots.retain(|slot| slot.id != id);
+                Some(String::new())
+            }
+            "slot-info" => {
+                let slots: Vec<serde_json::Value> = self
+                    .slots
+                    .iter()
+                    .map(|slot| {
+                        let reason = if slot.status.is_paused() {
+                            "by user"
+                        } else {
+                            ""
+                        };
+                        json!({
+                            "id": slot.id.to_string(),
+                            "status": slot.status.to_string(),
+                            "description": slot.description,
+                            "options": {"paused": slot.options.get("paused")},
+                            "reason": reason,
+                            "idle": slot.options.get("idle").map(String::as_str) == Some("true")
+                        })
+                    })
+                    .collect();
+                pyon_message("slots", &json!(slots))
+            }
+            "slot-options" => {
+                let slot = self.slot_mut(args.first())?;
+                match &args[1..] {
+                    [all] if all == "-a" => {
+                        pyon_message("slot-options", &options_value(slot.options.iter()))
+                    }
+                    [k, v] => {
+                        if k == "paused" {
+                            let status = if v == "true" {
+                                SlotStatus::Paused
+                            } else {
+                                SlotStatus::Running
+                            };
+                            slot.set_status(status);
+                        } else {
+                            slot.options.insert(k.clone(), v.clone());
+                        }
+                        Some(String::new())
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(
+            split_command(r#"options "proxy=a \"b\"" user=c"#).unwrap(),
+            vec!["options", r#"proxy=a "b""#, "user=c"]
+        );
+        assert_eq!(split_command("").unwrap(), Vec::<String>::new());
+        assert!(split_command(r#"auth "a"#).is_none());
+    }
+
+    #[test]
+    fn test_commands() {
+        let server = MockServer::start().unwrap();
+        let mut api =
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+
+        assert!(!api.help().unwrap().is_empty());
+        assert!(api.configured().unwrap());
+        api.screensaver().unwrap();
+        api.always_on(SlotId(0)).unwrap();
+        api.on_idle(SlotId(0)).unwrap();
+        api.on_idle_all().unwrap();
+        assert!(api.slot_info().unwrap()[0].idle);
+        api.do_cycle().unwrap();
+        api.request_id().unwrap();
+        api.request_ws().unwrap();
+        api.wait_for_units().unwrap();
+        assert_eq!(api.num_slots().unwrap(), 1);
+        assert_eq!(api.ppd().unwrap(), 248734.0);
+        assert_eq!(api.info_struct().unwrap().fah_client.version, "7.6.21");
+        assert_eq!(api.info().unwrap().len(), 4);
+        assert_eq!(
+            api.uptime().unwrap().0.unwrap().num_seconds(),
+            60 * 60 + 2 * 60 + 3
+        );
+        assert_eq!(
+            api.simulation_info(SlotId(0)).unwrap().iterations_done,
+            105000
+        );
+        assert!(api.simulation_info(SlotId(1)).is_err());
+        assert!(api
+            .log_updates(LogUpdatesArg::Start)
+            .unwrap()
+            .contains("Log Started"));
+
+        let mut buf: Vec<u8> = Vec::new();
+        api.conn.exec_eval("date", &mut buf).unwrap();
+        assert!(!buf.is_empty());
+        api.shutdown().unwrap();
+    }
+
+    #[test]
+    fn test_pause_unpause() {
+        let server = MockServer::start().unwrap();
+        let mut api =
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+
+        api.pause_all().unwrap();
+        assert_eq!(api.slot_info().unwrap()[0].status, SlotStatus::Paused);
+        assert_eq!(api.queue_info().unwrap()[0].state, QueueState::Ready);
+        assert!(api.slot_options_get(SlotId(0)).unwrap().paused.0);
+
+        api.unpause_slot(SlotId(0)).unwrap();
+        assert_eq!(api.slot_info().unwrap()[0].status, SlotStatus::Running);
+        assert_eq!(api.queue_info().unwrap()[0].state, QueueState::Running);
+
+        api.pause_slot(SlotId(0)).unwrap();
+        api.unpause_all().unwrap();
+        api.finish_slot(SlotId(0)).unwrap();
+        assert_eq!(api.slot_info().unwrap()[0].status, SlotStatus::Finishing);
+        api.finish_all().unwrap();
+
+        assert!(api.pause_slot(SlotId(1)).is_err());
+    }
+
+    #[test]
+    fn test_options() {
+        let server = MockServer::start().unwrap();
+        let mut api =
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+
+        let options = api.options_get().unwrap();
+        assert_eq!(options.power, Power::PowerMedium);
+        // None is read as an empty string
+        assert_eq!(options.extra_core_args, "");
+
+        api.options_set("power", Power::PowerLight).unwrap();
+        api.options_set("extra-core-args", "-a \"b c\"").unwrap();
+        let options = api.options_get().unwrap();
+        assert_eq!(options.power, Power::PowerLight);
+        assert_eq!(options.extra_core_args, "-a \"b c\"");
+
+        // Unknown option
+        assert!(matches!(api.options_set("a", ""), Err(Error::Disconnected)));
+        assert!(api.options_set("power=", Power::PowerNull).is_err());
+
+        api.slot_options_set(SlotId(0), "paused", true).unwrap();
+        assert!(api.slot_options_get(SlotId(0)).unwrap().paused.0);
+        assert_eq!(api.slot_info().unwrap()[0].status, SlotStatus::Paused);
+        api.slot_options_set(SlotId(0), "machine-id", 1).unwrap();
+        assert_eq!(api.slot_options_get(SlotId(0)).unwrap().machine_id, "1");
+        assert!(api.slot_options_get(SlotId(99)).is_err());
+
+        api.slot_delete(SlotId(0)).unwrap();
+        assert!(api.slot_info().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_slot_add_modify() {
+        let server = MockServer::start().unwrap();
+        let mut api =
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+
+        api.slot_add(SlotType::Gpu {
+            gpu_index: Some(1),
+            cuda_index: None,
+            opencl_index: None,
+        })
+        .unwrap();
+        let slots = api.slot_info().unwrap();
+        assert_eq!(slots.len(), 2);
+        assert_eq!(slots[1].id, SlotId(1));
+        assert_eq!(slots[1].description, "gpu:1");
+        assert_eq!(server.state().slots[1].options["gpu-index"], "1");
+
+        api.slot_modify(
+            SlotId(1),
+            SlotType::Cpu { cpus: Some(2) },
+            &[("paused", "true")],
+        )
+        .unwrap();
+        let slots = api.slot_info().unwrap();
+        assert_eq!(slots[1].description, "cpu:2");
+        assert_eq!(server.state().slots[1].options["cpus"], "2");
+        assert!(api.slot_options_get(SlotId(1)).unwrap().paused.0);
+
+        assert!(api
+            .slot_modify(SlotId(5), SlotType::Cpu { cpus: None }, &[])
+            .is_err());
+        api.slot_delete(SlotId(0)).unwrap();
+        api.slot_add(SlotType::Cpu { cpus: None }).unwrap();
+        let ids: Vec<SlotId> = api.slot_info().unwrap().iter().map(|s| s.id).collect();
+        assert_eq!(ids, [SlotId(1), SlotId(2)]);
+    }
+
+    #[test]
+    fn test_reconnect() {
+        let server = MockServer::start().unwrap();
+        let mut api =
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+
+        server.disconnect();
+        assert!(matches!(api.ppd(), Err(Error::Disconnected)));
+        api.ppd().unwrap();
+    }
+
+    #[test]
+    fn test_password() {
+        let server = MockServer::start().unwrap();
+        server.state().password = Some("a b".to_string());
+
+        assert!(matches!(
+            API::connect_with_password(&server.addr(), "wrong", core::time::Duration::from_secs(1)),
+            Err(Error::AuthRejected(_))
+        ));
+
+        let mut api =
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+        assert!(api.ppd().is_err());
+
+        let mut api =
+            API::connect_with_password(&server.addr(), "a b", core::time::Duration::from_secs(1))
+                .unwrap();
+        api.ppd().unwrap();
+
+        // The password is sent again after reconnecting
+        server.disconnect();
+        assert!(api.ppd().is_err());
+        api.ppd().unwrap();
+        assert_eq!(
+            server
+                .state()
+                .commands
+                .iter()
+                .filter(|c| *c == r#"auth "a b""#)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_responses() {
+        let server = MockServer::start().unwrap();
+        server
+            .state()
+            .responses
+            .insert("ppd".to_string(), "PyON 1 ppd\n1.5\n---".to_string());
+        assert_eq!(
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1))
+                .unwrap()
+                .ppd()
+                .unwrap(),
+            1.5
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async() {
+        let server = MockServer::start().unwrap();
+        let mut api = AsyncAPI::connect_timeout(&server.addr(), core::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        api.pause_all().await.unwrap();
+        assert_eq!(api.slot_info().await.unwrap()[0].status, SlotStatus::Paused);
+        assert_eq!(api.queue_info().await.unwrap().len(), 1);
+        assert_eq!(api.options_get().await.unwrap().user, "Anonymous");
+        api.uptime().await.unwrap();
+
+        server.disconnect();
+        assert!(matches!(api.ppd().await, Err(Error::Disconnected)));
+        api.ppd().await.unwrap();
+    }
+}
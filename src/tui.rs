@@ -0,0 +1,277 @@
+//! State and rendering of the `fahtop` terminal dashboard. Enable the `tui` feature to use this
+//! module.
+
+use super::*;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Gauge, Paragraph};
+use ratatui::Frame;
+
+/// Maximum number of log lines that are kept.
+const MAX_LOG_LINES: usize = 1000;
+
+/// State of the dashboard. [`refresh()`](#method.refresh) reloads the slots, queue and PPD from
+/// the client, and [`render()`](#method.render) draws them.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct App {
+    pub slots: Vec<SlotInfo>,
+    pub units: Vec<SlotQueueInfo>,
+    pub ppd: f64,
+    pub log: std::collections::VecDeque<String>,
+    /// Index of the selected slot
+    pub selected: usize,
+    /// Number of lines that the log pane is scrolled up from the bottom
+    pub log_scroll: usize,
+    /// The last error, which is shown in the status line
+    pub error: Option<String>,
+    pub quit: bool,
+    /// Text after the last newline, which is added to the log when the rest of the line arrives
+    partial_line: String,
+}
+
+impl App {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reloads the slots, queue and PPD. Errors are shown in the status line.
+    pub fn refresh(&mut self, api: &mut API) {
+        let result = (|| -> Result<()> {
+            self.slots = api.slot_info()?;
+            self.units = api.queue_info()?;
+            self.ppd = api.ppd()?;
+            Ok(())
+        })();
+
+        self.error = result.err().map(|e| e.to_string());
+        self.selected = self.selected.min(self.slots.len().saturating_sub(1));
+    }
+
+    /// Appends text from [`LogStream`](../struct.LogStream.html) to the log pane. The text may
+    /// end in the middle of a line.
+    pub fn append_log(&mut self, text: &str) {
+        let mut text = std::mem::take(&mut self.partial_line) + text;
+        match text.rfind('\n') {
+            Some(end) => self.partial_line = text.split_off(end + 1),
+            None => {
+                self.partial_line = text;
+                return;
+            }
+        }
+        self.log.extend(text.lines().map(str::to_string));
+        while self.log.len() > MAX_LOG_LINES {
+            self.log.pop_front();
+        }
+    }
+
+    /// Handles a key press. Commands for the selected slot are sent immediately and the slots are
+    /// refreshed afterwards.
+    pub fn handle_key(&mut self, key: KeyCode, api: &mut API) {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.slots.len().saturating_sub(1))
+            }
+            KeyCode::PageUp => {
+                self.log_scroll = (self.log_scroll + 10).min(self.log.len().saturating_sub(1))
+            }
+            KeyCode::PageDown => self.log_scroll = self.log_scroll.saturating_sub(10),
+            KeyCode::Char(c @ ('p' | 'u' | 'f')) => {
+                let slot = match self.selected_slot() {
+                    Some(slot) => slot,
+                    None => return,
+                };
+                let result = match c {
+                    'p' => api.pause_slot(slot),
+                    'u' => api.unpause_slot(slot),
+                    _ => api.finish_slot(slot),
+                };
+                match result {
+                    Ok(()) => self.refresh(api),
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the ID of the selected slot.
+    pub fn selected_slot(&self) -> Option<SlotId> {
+        Some(self.slots.get(self.selected)?.id)
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let slots_height = 2 + 2 * self.slots.len().max(1) as u16;
+        let [header, slots, log, help] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(slots_height),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let status = match &self.error {
+            Some(e) => Line::styled(format!("Error: {}", e), Style::default().fg(Color::Red)),
+            None => Line::from(format!("PPD: {}", self.ppd.round())),
+        };
+        frame.render_widget(Paragraph::new(status), header);
+
+        self.render_slots(frame, slots);
+
+        let block = Block::default().borders(Borders::ALL).title("Log");
+        let visible = block.inner(log).height as usize;
+        let end = self.log.len().saturating_sub(self.log_scroll);
+        let start = end.saturating_sub(visible);
+        let lines: Vec<Line> = self
+            .log
+            .range(start..end)
+            .map(|s| Line::from(s.as_str()))
+            .collect();
+        frame.render_widget(Paragraph::new(lines).block(block), log);
+
+        frame.render_widget(
+            Paragraph::new("q quit  ↑↓ select  p pause  u unpause  f finish  PgUp/PgDn scroll log"),
+            help,
+        );
+    }
+
+    fn render_slots(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::default().borders(Borders::ALL).title("Slots");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if self.slots.is_empty() {
+            frame.render_widget(Paragraph::new("No slots"), inner);
+            return;
+        }
+
+        let rows = Layout::vertical(vec![Constraint::Length(2); self.slots.len()]).split(inner);
+        for (i, (slot, row)) in self.slots.iter().zip(rows.iter()).enumerate() {
+            let [text, gauge] =
+                Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(*row);
+
+            let mut style = Style::default();
+            if i == self.selected {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+
+            let unit = self.units.iter().find(|unit| unit.slot == slot.id);
+            let mut description = format!("{} {:<10} {}", slot.id, slot.status, slot.description);
+            if let Some(unit) = unit {
+                description += &format!(
+                    "  {}  ETA {}  Credit {}",
+                    unit.prcg(),
+                    format_duration(unit.eta),
+                    unit.credit_estimate.0
+                );
+            }
+            frame.render_widget(Paragraph::new(description).style(style), text);
+
+            let percent = unit.map(|unit| unit.percent_done).unwrap_or_default();
+            frame.render_widget(
+                Gauge::default()
+                    .gauge_style(Style::default().fg(Color::Green))
+                    .ratio(percent.ratio().clamp(0.0, 1.0))
+                    .label(percent.to_string()),
+                gauge,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_app() {
+        use ratatui::backend::TestBackend;
+
+        let server = testing::MockServer::start().unwrap();
+        {
+            let mut state = server.state();
+            let mut slot = state.slots[0].clone();
+            slot.id = SlotId(1);
+            slot.description = "gpu:0:TU116".to_string();
+            state.slots.push(slot);
+        }
+        let mut api =
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+
+        let mut app = App::new();
+        app.refresh(&mut api);
+        assert_eq!(app.slots.len(), 2);
+        app.append_log("a\nb\n");
+
+        let mut terminal = ratatui::Terminal::new(TestBackend::new(100, 16)).unwrap();
+        let screen = |terminal: &mut ratatui::Terminal<TestBackend>, app: &App| -> String {
+            terminal.draw(|frame| app.render(frame)).unwrap();
+            let buffer = terminal.backend().buffer();
+            let mut s = String::new();
+            for y in 0..buffer.area.height {
+                for x in 0..buffer.area.width {
+                    s.push_str(buffer[(x, y)].symbol());
+                }
+                s.push('\n');
+            }
+            s
+        };
+
+        let s = screen(&mut terminal, &app);
+        assert!(s.contains("PPD: 248734"), "{}", s);
+        assert!(s.contains("00 RUNNING    cpu:4  P13424 R84 C7 G37  ETA 2h 13m  Credit 27510"));
+        assert!(s.contains("01 RUNNING    gpu:0:TU116"));
+        assert!(s.contains("42.17%"));
+        assert!(s.contains("│a"));
+        assert!(s.contains("│b"));
+
+        // Pause the second slot
+        app.handle_key(KeyCode::Down, &mut api);
+        app.handle_key(KeyCode::Down, &mut api);
+        assert_eq!(app.selected_slot(), Some(SlotId(1)));
+        app.handle_key(KeyCode::Char('p'), &mut api);
+        assert_eq!(server.state().slots[1].status, SlotStatus::Paused);
+        let s = screen(&mut terminal, &app);
+        assert!(s.contains("01 PAUSED     gpu:0:TU116"), "{}", s);
+
+        app.handle_key(KeyCode::Char('u'), &mut api);
+        assert_eq!(server.state().slots[1].status, SlotStatus::Running);
+        app.handle_key(KeyCode::Up, &mut api);
+        app.handle_key(KeyCode::Char('f'), &mut api);
+        assert_eq!(server.state().slots[0].status, SlotStatus::Finishing);
+
+        // Errors are shown instead of the PPD
+        server.state().slots.clear();
+        app.handle_key(KeyCode::Char('p'), &mut api);
+        assert!(app.error.is_some());
+        assert!(screen(&mut terminal, &app).contains("Error: "));
+        app.refresh(&mut api);
+        assert!(app.error.is_none());
+        assert_eq!(app.selected_slot(), None);
+        assert!(screen(&mut terminal, &app).contains("No slots"));
+
+        app.handle_key(KeyCode::Char('q'), &mut api);
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn test_append_log() {
+        let mut app = App::new();
+        for i in 0..MAX_LOG_LINES + 1 {
+            app.append_log(&format!("{}\n", i));
+        }
+        assert_eq!(app.log.len(), MAX_LOG_LINES);
+        assert_eq!(app.log.front().unwrap(), "1");
+
+        // A line that is split between chunks is one line
+        let mut app = App::new();
+        app.append_log("ab");
+        assert!(app.log.is_empty());
+        app.append_log("c\n");
+        assert_eq!(app.log, ["abc"]);
+    }
+}
@@ -1,131 +1,191 @@
 use super::*;
 use std::str::FromStr;
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize)]
-#[serde(rename_all = "kebab-case", default)]
-pub struct Options {
-    pub allow: String,
-    pub capture_directory: String,
-    pub capture_on_error: StringBool,
-    pub capture_packets: StringBool,
-    pub capture_requests: StringBool,
-    pub capture_responses: StringBool,
-    pub capture_sockets: StringBool,
-    pub cause: String,
-    pub certificate_file: String,
-    pub checkpoint: StringInt,
-    pub child: StringBool,
-    pub client_subtype: String,
-    pub client_threads: StringInt,
-    pub client_type: String,
-    pub command_address: String,
-    pub command_allow_no_pass: String,
-    pub deny: String,
-    pub command_deny_no_pass: String,
-    pub command_enable: StringBool,
-    pub command_port: StringInt,
-    pub config_rotate: StringBool,
-    pub config_rotate_dir: String,
-    pub config_rotate_max: StringInt,
-    pub connection_timeout: StringInt,
-    pub core_priority: String,
-    pub cpu_species: String,
-    pub cpu_type: String,
-    pub cpu_usage: StringInt,
-    pub cpus: StringInt,
-    pub crl_file: String,
-    pub cuda_index: String,
-    pub cycle_rate: StringInt,
-    pub cycles: StringInt,
-    pub daemon: StringBool,
-    pub debug_sockets: StringBool,
-    pub disable_sleep_when_active: StringBool,
-    pub disable_viz: StringBool,
-    pub dump_after_deadline: StringBool,
-    pub exception_locations: StringBool,
-    pub exit_when_done: StringBool,
-    pub extra_core_args: String,
-    pub fold_anon: String,
-    pub gpu: String,
-    pub gpu_index: String,
-    pub gpu_usage: StringInt,
-    pub gui_enabled: String,
-    pub http_addresses: String,
-    pub https_addresses: String,
-    pub idle: StringBool,
-    pub log: String,
-    pub log_color: StringBool,
-    pub log_crlf: StringBool,
-    pub log_date: StringBool,
-    pub log_date_periodically: StringInt,
-    pub log_domain: StringBool,
-    pub log_domain_levels: String,
-    pub log_header: StringBool,
-    pub log_level: StringBool,
-    pub log_no_info_header: StringBool,
-    pub log_redirect: StringBool,
-    pub log_rotate: StringBool,
-    pub log_rotate_dir: String,
-    pub log_rotate_max: StringInt,
-    pub log_short_level: StringBool,
-    pub log_simple_domains: StringBool,
-    pub log_thread_id: StringBool,
-    pub log_thread_prefix: StringBool,
-    pub log_time: StringBool,
-    pub log_to_screen: StringBool,
-    pub log_truncate: StringBool,
-    pub machine_id: StringInt,
-    pub max_connect_time: StringInt,
-    pub max_connections: StringInt,
-    pub max_packet_size: String,
-    pub max_queue: StringInt,
-    pub max_request_length: StringInt,
-    pub max_shutdown_wait: StringInt,
-    pub max_slot_errors: StringInt,
-    pub max_unit_errors: StringInt,
-    pub max_units: StringInt,
-    pub memory: String,
-    pub min_connect_time: StringInt,
-    pub next_unit_percentage: StringInt,
-    pub priority: String,
-    pub no_assembly: StringBool,
-    pub open_web_control: StringBool,
-    pub opencl_index: String,
-    pub os_species: String,
-    pub os_type: String,
-    pub passkey: String,
-    pub password: String,
-    pub pause_on_battery: StringBool,
-    pub pause_on_start: StringBool,
-    pub paused: StringBool,
-    pub pid: StringBool,
-    pub pid_file: String,
-    pub power: Power,
-    pub private_key_file: String,
-    pub project_key: StringInt,
-    pub proxy: String,
-    pub proxy_enable: StringBool,
-    pub proxy_pass: String,
-    pub proxy_user: String,
-    pub respawn: StringBool,
-    pub service: StringBool,
-    pub service_description: String,
-    pub service_restart: StringBool,
-    pub service_restart_delay: StringInt,
-    pub session_cookie: String,
-    pub session_lifetime: StringInt,
-    pub session_timeout: StringInt,
-    pub smp: StringBool,
-    pub stack_traces: StringBool,
-    pub stall_detection_enabled: StringBool,
-    pub stall_percent: StringInt,
-    pub stall_timeout: StringInt,
-    pub team: StringInt,
-    pub user: String,
-    pub verbosity: StringInt,
-    pub web_allow: String,
-    pub web_deny: String,
-    pub web_enable: StringBool,
+/// Defines `Options` and an `OptionKey` variant for each of its fields.
+macro_rules! options {
+    ($($field:ident: $ty:ty => $variant:ident,)*) => {
+        #[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize, serde::Serialize)]
+        #[serde(rename_all = "kebab-case", default)]
+        pub struct Options {
+            $(pub $field: $ty,)*
+        }
+
+        /// An option of the FAH client with its value, for
+        /// [`API::set_option`](./struct.API.html#method.set_option). There is a variant for each
+        /// field of [`Options`](./struct.Options.html).
+        #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+        pub enum OptionKey {
+            $($variant($ty),)*
+        }
+
+        impl OptionKey {
+            /// Returns the name of the option, like "cpu-usage".
+            pub fn key(&self) -> String {
+                match self {
+                    $(OptionKey::$variant(_) => stringify!($field).replace('_', "-"),)*
+                }
+            }
+
+            /// Returns the value as it is sent to the FAH client.
+            pub fn value(&self) -> String {
+                match self {
+                    $(OptionKey::$variant(v) => v.to_string(),)*
+                }
+            }
+
+            /// Sets the field of `options` to the value.
+            pub fn apply(&self, options: &mut Options) {
+                match self {
+                    $(OptionKey::$variant(v) => options.$field = v.clone(),)*
+                }
+            }
+        }
+    };
+}
+
+options! {
+    allow: String => Allow,
+    capture_directory: String => CaptureDirectory,
+    capture_on_error: StringBool => CaptureOnError,
+    capture_packets: StringBool => CapturePackets,
+    capture_requests: StringBool => CaptureRequests,
+    capture_responses: StringBool => CaptureResponses,
+    capture_sockets: StringBool => CaptureSockets,
+    cause: String => Cause,
+    certificate_file: String => CertificateFile,
+    checkpoint: StringInt => Checkpoint,
+    child: StringBool => Child,
+    client_subtype: String => ClientSubtype,
+    client_threads: StringInt => ClientThreads,
+    client_type: String => ClientType,
+    command_address: String => CommandAddress,
+    command_allow_no_pass: String => CommandAllowNoPass,
+    deny: String => Deny,
+    command_deny_no_pass: String => CommandDenyNoPass,
+    command_enable: StringBool => CommandEnable,
+    command_port: StringInt => CommandPort,
+    config_rotate: StringBool => ConfigRotate,
+    config_rotate_dir: String => ConfigRotateDir,
+    config_rotate_max: StringInt => ConfigRotateMax,
+    connection_timeout: StringInt => ConnectionTimeout,
+    core_priority: String => CorePriority,
+    cpu_species: String => CpuSpecies,
+    cpu_type: String => CpuType,
+    cpu_usage: StringInt => CpuUsage,
+    cpus: StringInt => Cpus,
+    crl_file: String => CrlFile,
+    cuda_index: String => CudaIndex,
+    cycle_rate: StringInt => CycleRate,
+    cycles: StringInt => Cycles,
+    daemon: StringBool => Daemon,
+    debug_sockets: StringBool => DebugSockets,
+    disable_sleep_when_active: StringBool => DisableSleepWhenActive,
+    disable_viz: StringBool => DisableViz,
+    dump_after_deadline: StringBool => DumpAfterDeadline,
+    exception_locations: StringBool => ExceptionLocations,
+    exit_when_done: StringBool => ExitWhenDone,
+    extra_core_args: String => ExtraCoreArgs,
+    fold_anon: String => FoldAnon,
+    gpu: String => Gpu,
+    gpu_index: String => GpuIndex,
+    gpu_usage: StringInt => GpuUsage,
+    gui_enabled: String => GuiEnabled,
+    http_addresses: String => HttpAddresses,
+    https_addresses: String => HttpsAddresses,
+    idle: StringBool => Idle,
+    log: String => Log,
+    log_color: StringBool => LogColor,
+    log_crlf: StringBool => LogCrlf,
+    log_date: StringBool => LogDate,
+    log_date_periodically: StringInt => LogDatePeriodically,
+    log_domain: StringBool => LogDomain,
+    log_domain_levels: String => LogDomainLevels,
+    log_header: StringBool => LogHeader,
+    log_level: StringBool => LogLevel,
+    log_no_info_header: StringBool => LogNoInfoHeader,
+    log_redirect: StringBool => LogRedirect,
+    log_rotate: StringBool => LogRotate,
+    log_rotate_dir: String => LogRotateDir,
+    log_rotate_max: StringInt => LogRotateMax,
+    log_short_level: StringBool => LogShortLevel,
+    log_simple_domains: StringBool => LogSimpleDomains,
+    log_thread_id: StringBool => LogThreadId,
+    log_thread_prefix: StringBool => LogThreadPrefix,
+    log_time: StringBool => LogTime,
+    log_to_screen: StringBool => LogToScreen,
+    log_truncate: StringBool => LogTruncate,
+    machine_id: StringInt => MachineId,
+    max_connect_time: StringInt => MaxConnectTime,
+    max_connections: StringInt => MaxConnections,
+    max_packet_size: String => MaxPacketSize,
+    max_queue: StringInt => MaxQueue,
+    max_request_length: StringInt => MaxRequestLength,
+    max_shutdown_wait: StringInt => MaxShutdownWait,
+    max_slot_errors: StringInt => MaxSlotErrors,
+    max_unit_errors: StringInt => MaxUnitErrors,
+    max_units: StringInt => MaxUnits,
+    memory: String => Memory,
+    min_connect_time: StringInt => MinConnectTime,
+    next_unit_percentage: StringInt => NextUnitPercentage,
+    priority: String => Priority,
+    no_assembly: StringBool => NoAssembly,
+    open_web_control: StringBool => OpenWebControl,
+    opencl_index: String => OpenclIndex,
+    os_species: String => OsSpecies,
+    os_type: String => OsType,
+    passkey: String => Passkey,
+    password: String => Password,
+    pause_on_battery: StringBool => PauseOnBattery,
+    pause_on_start: StringBool => PauseOnStart,
+    paused: StringBool => Paused,
+    pid: StringBool => Pid,
+    pid_file: String => PidFile,
+    power: Power => Power,
+    private_key_file: String => PrivateKeyFile,
+    project_key: StringInt => ProjectKey,
+    proxy: String => Proxy,
+    proxy_enable: StringBool => ProxyEnable,
+    proxy_pass: String => ProxyPass,
+    proxy_user: String => ProxyUser,
+    respawn: StringBool => Respawn,
+    service: StringBool => Service,
+    service_description: String => ServiceDescription,
+    service_restart: StringBool => ServiceRestart,
+    service_restart_delay: StringInt => ServiceRestartDelay,
+    session_cookie: String => SessionCookie,
+    session_lifetime: StringInt => SessionLifetime,
+    session_timeout: StringInt => SessionTimeout,
+    smp: StringBool => Smp,
+    stack_traces: StringBool => StackTraces,
+    stall_detection_enabled: StringBool => StallDetectionEnabled,
+    stall_percent: StringInt => StallPercent,
+    stall_timeout: StringInt => StallTimeout,
+    team: StringInt => Team,
+    user: String => User,
+    verbosity: StringInt => Verbosity,
+    web_allow: String => WebAllow,
+    web_deny: String => WebDeny,
+    web_enable: StringBool => WebEnable,
+}
+
+impl OptionKey {
+    /// Returns an error if the value is out of range for the option.
+    pub fn validate(&self) -> Result<()> {
+        let in_range = match self {
+            OptionKey::CpuUsage(n) | OptionKey::GpuUsage(n) => (0..=100).contains(&n.0),
+            OptionKey::Team(n) | OptionKey::Cpus(n) | OptionKey::MachineId(n) => n.0 >= 0,
+            OptionKey::Power(p) => *p != Power::PowerNull,
+            _ => true,
+        };
+        if !in_range {
+            return Err(Error::InvalidCommand(format!(
+                "invalid value for {}: {}",
+                self.key(),
+                self.value()
+            )));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
@@ -148,17 +208,29 @@ impl<'de> serde::de::Deserialize<'de> for StringBool {
     where
         D: serde::Deserializer<'de>,
     {
-        match serde_json::from_str(serde::de::Deserialize::deserialize(deserializer)?) {
+        let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+        match serde_json::from_str(&s) {
             Ok(result) => Ok(Self(result)),
             Err(e) => Err(serde::de::Error::custom(e.to_string())),
         }
     }
 }
 
+impl serde::Serialize for StringBool {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 impl core::str::FromStr for StringBool {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self> {
-        Ok(Self(str::parse(s)?))
+        Ok(Self(str::parse(s).map_err(|e| {
+            Error::UnexpectedShape(format!("{}: {}", e, s))
+        })?))
     }
 }
 
@@ -182,34 +254,41 @@ impl<'de> serde::de::Deserialize<'de> for StringInt {
     where
         D: serde::Deserializer<'de>,
     {
-        match serde_json::from_str(serde::de::Deserialize::deserialize(deserializer)?) {
+        let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+        match serde_json::from_str(&s) {
             Ok(n) => Ok(Self(n)),
             Err(e) => Err(serde::de::Error::custom(e.to_string())),
         }
     }
 }
 
+impl serde::Serialize for StringInt {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 impl core::str::FromStr for StringInt {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self> {
-        Ok(Self(str::parse(s)?))
+        Ok(Self(str::parse(s).map_err(|e| {
+            Error::UnexpectedShape(format!("{}: {}", e, s))
+        })?))
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
 pub enum Power {
+    #[default]
     PowerNull,
     PowerLight,
     PowerMedium,
     PowerFull,
 }
 
-impl std::default::Default for Power {
-    fn default() -> Self {
-        Power::PowerNull
-    }
-}
-
 impl core::str::FromStr for Power {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self> {
@@ -218,7 +297,7 @@ impl core::str::FromStr for Power {
             "LIGHT" => Power::PowerLight,
             "MEDIUM" => Power::PowerMedium,
             "FULL" => Power::PowerFull,
-            _ => return Err(Error::msg(format!("s is invalid: {}", s))),
+            _ => return Err(Error::UnexpectedShape(format!("s is invalid: {}", s))),
         })
     }
 }
@@ -240,26 +319,423 @@ impl<'de> serde::de::Deserialize<'de> for Power {
     where
         D: serde::Deserializer<'de>,
     {
-        match str::parse(serde::de::Deserialize::deserialize(deserializer)?) {
+        let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+        match str::parse(&s) {
             Ok(p) => Ok(p),
             Err(e) => Err(serde::de::Error::custom(e.to_string())),
         }
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize)]
+impl serde::Serialize for Power {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// Status of a folding slot.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SlotStatus {
+    Ready,
+    Running,
+    Finishing,
+    Paused,
+    Stopping,
+    Failed,
+    /// Any other status, including the empty string
+    Unknown(String),
+}
+
+impl SlotStatus {
+    /// Returns true if the slot is folding.
+    pub fn is_active(&self) -> bool {
+        matches!(self, SlotStatus::Running | SlotStatus::Finishing)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        matches!(self, SlotStatus::Paused)
+    }
+}
+
+impl Default for SlotStatus {
+    fn default() -> Self {
+        SlotStatus::Unknown(String::new())
+    }
+}
+
+impl core::str::FromStr for SlotStatus {
+    type Err = core::convert::Infallible;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "READY" => SlotStatus::Ready,
+            "RUNNING" => SlotStatus::Running,
+            "FINISHING" => SlotStatus::Finishing,
+            "PAUSED" => SlotStatus::Paused,
+            "STOPPING" => SlotStatus::Stopping,
+            "FAILED" => SlotStatus::Failed,
+            _ => SlotStatus::Unknown(s.to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for SlotStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SlotStatus::Ready => "READY",
+            SlotStatus::Running => "RUNNING",
+            SlotStatus::Finishing => "FINISHING",
+            SlotStatus::Paused => "PAUSED",
+            SlotStatus::Stopping => "STOPPING",
+            SlotStatus::Failed => "FAILED",
+            SlotStatus::Unknown(s) => s,
+        };
+        f.pad(s)
+    }
+}
+
+impl<'de> serde::de::Deserialize<'de> for SlotStatus {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+        Ok(s.parse().unwrap_or_else(|e| match e {}))
+    }
+}
+
+impl serde::Serialize for SlotStatus {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// State of a work unit in the queue.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum QueueState {
+    Ready,
+    Download,
+    Running,
+    Finished,
+    Send,
+    Archived,
+    Delete,
+    Failed,
+    /// Any other state, including the empty string
+    Unknown(String),
+}
+
+impl QueueState {
+    /// Returns true if the work unit is being folded.
+    pub fn is_active(&self) -> bool {
+        matches!(self, QueueState::Running)
+    }
+
+    /// Returns true if the work unit is waiting to be folded, which is the state of work units in
+    /// paused slots.
+    pub fn is_paused(&self) -> bool {
+        matches!(self, QueueState::Ready)
+    }
+
+    /// Returns true if the work unit is being downloaded or its results are being sent.
+    pub fn is_transferring(&self) -> bool {
+        matches!(self, QueueState::Download | QueueState::Send)
+    }
+}
+
+impl Default for QueueState {
+    fn default() -> Self {
+        QueueState::Unknown(String::new())
+    }
+}
+
+impl core::str::FromStr for QueueState {
+    type Err = core::convert::Infallible;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "READY" => QueueState::Ready,
+            "DOWNLOAD" => QueueState::Download,
+            "RUNNING" => QueueState::Running,
+            "FINISHED" => QueueState::Finished,
+            "SEND" => QueueState::Send,
+            "ARCHIVED" => QueueState::Archived,
+            "DELETE" => QueueState::Delete,
+            "FAILED" => QueueState::Failed,
+            _ => QueueState::Unknown(s.to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for QueueState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            QueueState::Ready => "READY",
+            QueueState::Download => "DOWNLOAD",
+            QueueState::Running => "RUNNING",
+            QueueState::Finished => "FINISHED",
+            QueueState::Send => "SEND",
+            QueueState::Archived => "ARCHIVED",
+            QueueState::Delete => "DELETE",
+            QueueState::Failed => "FAILED",
+            QueueState::Unknown(s) => s,
+        };
+        f.pad(s)
+    }
+}
+
+impl<'de> serde::de::Deserialize<'de> for QueueState {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+        Ok(s.parse().unwrap_or_else(|e| match e {}))
+    }
+}
+
+impl serde::Serialize for QueueState {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// ID of a folding slot. The FAH client sends it as a zero-padded string like "00", and Display
+/// formats it the same way.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct SlotId(pub u32);
+
+impl From<u32> for SlotId {
+    fn from(n: u32) -> Self {
+        Self(n)
+    }
+}
+
+impl std::fmt::Display for SlotId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&format!("{:02}", self.0))
+    }
+}
+
+impl core::str::FromStr for SlotId {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Self(parse_id(s)?))
+    }
+}
+
+impl<'de> serde::de::Deserialize<'de> for SlotId {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(Self(deserialize_id(deserializer)?))
+    }
+}
+
+impl serde::Serialize for SlotId {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// ID of a work unit in the queue. The FAH client sends it as a zero-padded string like "01", and
+/// Display formats it the same way.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct QueueId(pub u32);
+
+impl From<u32> for QueueId {
+    fn from(n: u32) -> Self {
+        Self(n)
+    }
+}
+
+impl std::fmt::Display for QueueId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&format!("{:02}", self.0))
+    }
+}
+
+impl core::str::FromStr for QueueId {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Self(parse_id(s)?))
+    }
+}
+
+impl<'de> serde::de::Deserialize<'de> for QueueId {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(Self(deserialize_id(deserializer)?))
+    }
+}
+
+impl serde::Serialize for QueueId {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+fn parse_id(s: &str) -> Result<u32> {
+    str::parse(s).map_err(|e| Error::UnexpectedShape(format!("{}: {}", e, s)))
+}
+
+/// Deserializes an ID from a string like "01" or a number.
+fn deserialize_id<'de, D>(deserializer: D) -> std::result::Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u32),
+        String(String),
+    }
+
+    match serde::de::Deserialize::deserialize(deserializer)? {
+        Id::Number(n) => Ok(n),
+        Id::String(s) => parse_id(&s).map_err(|e| serde::de::Error::custom(e.to_string())),
+    }
+}
+
+/// Percentage from 0 to 100, such as the progress of a work unit. The FAH client sends it as a
+/// string like "42.17%", and Display formats it the same way.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default)]
+pub struct Percent(pub f64);
+
+impl Percent {
+    /// Returns the percentage as a fraction from 0 to 1.
+    pub fn ratio(self) -> f64 {
+        self.0 / 100.0
+    }
+}
+
+impl From<f64> for Percent {
+    fn from(n: f64) -> Self {
+        Self(n)
+    }
+}
+
+impl std::fmt::Display for Percent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&format!("{:.2}%", self.0))
+    }
+}
+
+impl core::str::FromStr for Percent {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let n = s.strip_suffix('%').unwrap_or(s);
+        Ok(Self(str::parse(n).map_err(|e| {
+            Error::UnexpectedShape(format!("{}: {}", e, s))
+        })?))
+    }
+}
+
+impl<'de> serde::de::Deserialize<'de> for Percent {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+        str::parse(&s).map_err(|e: Error| serde::de::Error::custom(e.to_string()))
+    }
+}
+
+impl serde::Serialize for Percent {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        // Display rounds to 2 decimals
+        serializer.collect_str(&format_args!("{}%", self.0))
+    }
+}
+
+/// 128-bit ID of a work unit, which the FAH client sends as a hex string like
+/// "0x0000002a287234c95e8a93ff63ec35b1". An empty string is parsed as zero.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct UnitId(pub [u8; 16]);
+
+impl std::fmt::Display for UnitId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut s = String::with_capacity(34);
+        s.push_str("0x");
+        for b in &self.0 {
+            s.push_str(&format!("{:02x}", b));
+        }
+        f.pad(&s)
+    }
+}
+
+impl core::str::FromStr for UnitId {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let err = || Error::UnexpectedShape(format!("invalid unit ID: {}", s));
+
+        let mut result = [0u8; 16];
+        if s.is_empty() {
+            return Ok(Self(result));
+        }
+
+        let hex = s.strip_prefix("0x").ok_or_else(err)?;
+        if hex.len() != 32 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(err());
+        }
+        for (i, b) in result.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| err())?;
+        }
+        Ok(Self(result))
+    }
+}
+
+impl<'de> serde::de::Deserialize<'de> for UnitId {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+        str::parse(&s).map_err(|e: Error| serde::de::Error::custom(e.to_string()))
+    }
+}
+
+impl serde::Serialize for UnitId {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case", default)]
 pub struct SlotQueueInfo {
-    pub id: String,
-    pub state: String,
+    pub id: QueueId,
+    pub state: QueueState,
     pub error: String,
     pub project: i64,
     pub run: i64,
     pub clone: i64,
     pub gen: i64,
     pub core: String,
-    pub unit: String,
-    pub percent_done: String,
+    pub unit: UnitId,
+    pub percent_done: Percent,
     pub eta: FAHDuration,
     pub ppd: StringInt,
     pub credit_estimate: StringInt,
@@ -274,11 +750,69 @@ pub struct SlotQueueInfo {
     pub ws: String,
     pub cs: String,
     pub attempts: i64,
-    pub slot: String,
+    pub slot: SlotId,
     pub tpf: FAHDuration,
     pub base_credit: StringInt,
 }
 
+impl SlotQueueInfo {
+    pub fn prcg(&self) -> PRCG {
+        PRCG {
+            project: self.project,
+            run: self.run,
+            clone: self.clone,
+            gen: self.gen,
+        }
+    }
+}
+
+/// Project, run, clone and gen numbers that identify a work unit, formatted like
+/// `P13424 R84 C7 G37`. The ordering compares the numbers in that order.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct PRCG {
+    pub project: i64,
+    pub run: i64,
+    pub clone: i64,
+    pub gen: i64,
+}
+
+impl std::fmt::Display for PRCG {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&format!(
+            "P{} R{} C{} G{}",
+            self.project, self.run, self.clone, self.gen
+        ))
+    }
+}
+
+impl core::str::FromStr for PRCG {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let err = || Error::UnexpectedShape(format!("invalid PRCG: {}", s));
+
+        let mut numbers = [0; 4];
+        let mut words = s.split_whitespace();
+        for (n, prefix) in numbers.iter_mut().zip(['P', 'R', 'C', 'G']) {
+            let word = words.next().ok_or_else(err)?;
+            *n = word
+                .strip_prefix(prefix)
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(err)?;
+        }
+        if words.next().is_some() {
+            return Err(err());
+        }
+
+        let [project, run, clone, gen] = numbers;
+        Ok(Self {
+            project,
+            run,
+            clone,
+            gen,
+        })
+    }
+}
+
 /// None means invalid time.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
 pub struct FAHTime(pub Option<chrono::DateTime<chrono::offset::Utc>>);
@@ -311,18 +845,32 @@ impl<'de> serde::de::Deserialize<'de> for FAHTime {
     where
         D: serde::Deserializer<'de>,
     {
-        let s = serde::de::Deserialize::deserialize(deserializer)?;
+        let s: String = serde::de::Deserialize::deserialize(deserializer)?;
         if s == INVALID_TIME {
             return Ok(None.into());
         }
 
-        match chrono::DateTime::parse_from_rfc3339(s) {
+        match chrono::DateTime::parse_from_rfc3339(&s) {
             Ok(t) => Ok(t.with_timezone(&chrono::offset::Utc).into()),
             Err(e) => Err(serde::de::Error::custom(e.to_string())),
         }
     }
 }
 
+impl serde::Serialize for FAHTime {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.0 {
+            Some(t) => {
+                serializer.serialize_str(&t.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+            }
+            None => serializer.serialize_str(INVALID_TIME),
+        }
+    }
+}
+
 /// None means unknown duration.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
 pub struct FAHDuration(pub Option<chrono::Duration>);
@@ -355,7 +903,8 @@ impl<'de> serde::de::Deserialize<'de> for FAHDuration {
     where
         D: serde::Deserializer<'de>,
     {
-        let s: &str = serde::de::Deserialize::deserialize(deserializer)?;
+        let s: String = serde::de::Deserialize::deserialize(deserializer)?;
+        let s = s.as_str();
         if s == UNKNOWN_TIME {
             return Ok(None.into());
         }
@@ -391,7 +940,38 @@ impl<'de> serde::de::Deserialize<'de> for FAHDuration {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Default, serde::Deserialize)]
+impl serde::Serialize for FAHDuration {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.0 {
+            // Formatted like "1h 2m 3s" so that humantime can parse it
+            Some(d) => {
+                let d = d
+                    .to_std()
+                    .map_err(|e| serde::ser::Error::custom(e.to_string()))?;
+                serializer.collect_str(&humantime::format_duration(d))
+            }
+            None => serializer.serialize_str(UNKNOWN_TIME),
+        }
+    }
+}
+
+/// Formats a duration like "2h 13m" for display. Durations of a minute or more are rounded down
+/// to minutes.
+pub fn format_duration(d: FAHDuration) -> String {
+    match d.0.and_then(|d| d.to_std().ok()) {
+        Some(d) => {
+            let secs = d.as_secs();
+            let secs = if secs >= 60 { secs - secs % 60 } else { secs };
+            humantime::format_duration(core::time::Duration::from_secs(secs)).to_string()
+        }
+        None => "unknown".to_string(),
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case", default)]
 pub struct SimulationInfo {
     pub user: String,
@@ -411,28 +991,154 @@ pub struct SimulationInfo {
     pub deadline: i64,
     pub eta: i64,
     pub progress: f64,
-    pub slot: i64,
+    pub slot: SlotId,
+}
+
+impl SimulationInfo {
+    pub fn prcg(&self) -> PRCG {
+        PRCG {
+            project: self.project,
+            run: self.run,
+            clone: self.clone,
+            gen: self.gen,
+        }
+    }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, Default, serde::Deserialize)]
+#[derive(Clone, PartialEq, Eq, Debug, Default, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case", default)]
 pub struct SlotInfo {
-    pub id: String,
-    pub status: String,
+    pub id: SlotId,
+    pub status: SlotStatus,
     pub description: String,
     pub options: serde_json::map::Map<String, serde_json::Value>,
     pub reason: String,
     pub idle: bool,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, Default, serde::Deserialize)]
-#[serde(rename_all = "kebab-case", default)]
-pub struct SlotOptions {
-    pub machine_id: String,
-    pub paused: StringBool,
+/// Type of a slot for [`API::slot_add`](./struct.API.html#method.slot_add). Fields that are None
+/// are left to the client's defaults.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SlotType {
+    Cpu {
+        cpus: Option<u32>,
+    },
+    Gpu {
+        gpu_index: Option<u32>,
+        cuda_index: Option<u32>,
+        opencl_index: Option<u32>,
+    },
+}
+
+impl SlotType {
+    /// Returns the slot options that are set by the type, like ("cpus", "4").
+    pub fn options(&self) -> Vec<(&'static str, String)> {
+        let options = match self {
+            SlotType::Cpu { cpus } => vec![("cpus", cpus)],
+            SlotType::Gpu {
+                gpu_index,
+                cuda_index,
+                opencl_index,
+            } => vec![
+                ("gpu-index", gpu_index),
+                ("cuda-index", cuda_index),
+                ("opencl-index", opencl_index),
+            ],
+        };
+        options
+            .into_iter()
+            .filter_map(|(k, v)| Some((k, v.as_ref()?.to_string())))
+            .collect()
+    }
+}
+
+/// Formats the type as "cpu" or "gpu", like the `slot-add` command takes it.
+impl std::fmt::Display for SlotType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SlotType::Cpu { .. } => "cpu",
+            SlotType::Gpu { .. } => "gpu",
+        };
+        f.pad(s)
+    }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize)]
+/// Defines `SlotOptions` with a field for each option, and `extra` for the other options.
+macro_rules! slot_options {
+    ($($field:ident: $ty:ty,)*) => {
+        /// Options of a slot, as returned by `slot-options <slot> -a`.
+        #[derive(Clone, PartialEq, Eq, Debug, Default, serde::Serialize)]
+        #[serde(rename_all = "kebab-case")]
+        pub struct SlotOptions {
+            $(pub $field: $ty,)*
+            /// Options that do not have a field, by their kebab-case names
+            #[serde(flatten)]
+            pub extra: std::collections::BTreeMap<String, String>,
+        }
+
+        impl SlotOptions {
+            /// Sets the field with the kebab-case name, or adds the option to `extra`. Fields are
+            /// left unchanged if the value is empty.
+            fn set(&mut self, key: &str, value: String) -> Result<()> {
+                $(
+                    if key == stringify!($field).replace('_', "-") {
+                        if !value.is_empty() {
+                            self.$field = value
+                                .parse()
+                                .map_err(|e| Error::UnexpectedShape(format!("{}: {}", key, e)))?;
+                        }
+                        return Ok(());
+                    }
+                )*
+                self.extra.insert(key.to_string(), value);
+                Ok(())
+            }
+        }
+    };
+}
+
+slot_options! {
+    checkpoint: StringInt,
+    client_subtype: String,
+    client_type: String,
+    core_priority: String,
+    cpu_usage: StringInt,
+    cuda_index: String,
+    gpu_index: String,
+    gpu_usage: StringInt,
+    machine_id: String,
+    max_packet_size: String,
+    max_units: StringInt,
+    next_unit_percentage: StringInt,
+    opencl_index: String,
+    pause_on_start: StringBool,
+    paused: StringBool,
+}
+
+/// None is read as an empty string.
+impl<'de> serde::de::Deserialize<'de> for SlotOptions {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let map: std::collections::BTreeMap<String, Option<serde_json::Value>> =
+            serde::de::Deserialize::deserialize(deserializer)?;
+        let mut options = Self::default();
+        for (k, v) in map {
+            let v = match v {
+                None => String::new(),
+                Some(serde_json::Value::String(s)) => s,
+                Some(v) => v.to_string(),
+            };
+            options
+                .set(&k, v)
+                .map_err(|e| serde::de::Error::custom(e.to_string()))?;
+        }
+        Ok(options)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct Info {
     pub fah_client: FAHClient,
     pub cbang: CBang,
@@ -443,7 +1149,7 @@ pub struct Info {
 impl Info {
     pub fn new(src: Vec<Vec<serde_json::Value>>) -> Result<Self> {
         if src.len() < 4 {
-            return Err(Error::msg(format!(
+            return Err(Error::UnexpectedShape(format!(
                 "src should have 4 arrays but has {}",
                 src.len()
             )));
@@ -456,7 +1162,7 @@ impl Info {
             let field: &mut dyn FieldSetter = match row_iter
                 .next()
                 .and_then(|e| e.as_str())
-                .ok_or_else(|| Error::msg("unexpected type"))?
+                .ok_or_else(|| Error::UnexpectedShape("unexpected type".to_string()))?
             {
                 "FAHClient" => &mut info.fah_client,
                 "CBang" => &mut info.cbang,
@@ -469,15 +1175,17 @@ impl Info {
             };
 
             for v in row_iter {
-                let entry = v.as_array().ok_or_else(|| Error::msg("unexpected type"))?;
+                let entry = v
+                    .as_array()
+                    .ok_or_else(|| Error::UnexpectedShape("unexpected type".to_string()))?;
                 let k = entry
-                    .get(0)
+                    .first()
                     .and_then(|k| k.as_str())
-                    .ok_or_else(|| Error::msg("unexpected type"))?;
+                    .ok_or_else(|| Error::UnexpectedShape("unexpected type".to_string()))?;
                 let v = entry
                     .get(1)
                     .and_then(|v| v.as_str())
-                    .ok_or_else(|| Error::msg("unexpected type"))?;
+                    .ok_or_else(|| Error::UnexpectedShape("unexpected type".to_string()))?;
                 field.set(k, v)?;
             }
         }
@@ -490,7 +1198,7 @@ trait FieldSetter {
     fn set(&mut self, k: &str, value: &str) -> Result<()>;
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct FAHClient {
     pub version: String,
     pub author: String,
@@ -534,7 +1242,7 @@ impl FieldSetter for FAHClient {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct CBang {
     pub date: String,
     pub time: String,
@@ -566,24 +1274,31 @@ impl FieldSetter for CBang {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct System {
     pub cpu: String,
     pub cpu_id: String,
     pub cpus: StringInt,
-    pub memory: String,
-    pub free_memory: String,
+    /// Bytes
+    pub memory: u64,
+    /// Bytes
+    pub free_memory: u64,
     pub threads: String,
     pub os_version: String,
-    pub has_battery: String,
-    pub on_battery: String,
+    pub has_battery: bool,
+    pub on_battery: bool,
     pub utc_offset: String,
     pub pid: String,
     pub cwd: String,
     pub os: String,
     pub os_arch: String,
     pub gpus: StringInt,
-    // I don't have multiple GPUs so I can't test the "GPU 0" field
+    /// From the "GPU n" fields
+    pub gpu_devices: Vec<GpuInfo>,
+    /// From the "CUDA Device n" fields
+    pub cuda_devices: Vec<ComputeDevice>,
+    /// From the "OpenCL Device n" fields
+    pub opencl_devices: Vec<ComputeDevice>,
 }
 
 impl FieldSetter for System {
@@ -593,20 +1308,31 @@ impl FieldSetter for System {
             "CPU" => self.cpu = v,
             "CPU ID" => self.cpu_id = v,
             "CPUs" => self.cpus = str::parse(value)?,
-            "Memory" => self.memory = v,
-            "Free Memory" => self.free_memory = v,
+            "Memory" => self.memory = parse_bytes(value)?,
+            "Free Memory" => self.free_memory = parse_bytes(value)?,
             "Threads" => self.threads = v,
             "OS Version" => self.os_version = v,
-            "Has Battery" => self.has_battery = v,
-            "On Battery" => self.on_battery = v,
+            "Has Battery" => self.has_battery = parse_bool(value)?,
+            "On Battery" => self.on_battery = parse_bool(value)?,
             "UTC Offset" => self.utc_offset = v,
             "PID" => self.pid = v,
             "CWD" => self.cwd = v,
             "OS" => self.os = v,
             "OS Arch" => self.os_arch = v,
             "GPUs" => self.gpus = str::parse(value)?,
+            // "Not detected: ..." when there is no CUDA or OpenCL support
+            "CUDA" | "OpenCL" => (),
             _ => {
-                if !k.starts_with("GPU") && !k.starts_with("CUDA") && !k.starts_with("OpenCL") {
+                if let Some(index) = k.strip_prefix("GPU ") {
+                    self.gpu_devices
+                        .push(GpuInfo::new(parse_index(index)?, value)?);
+                } else if let Some(index) = k.strip_prefix("CUDA Device ") {
+                    self.cuda_devices
+                        .push(ComputeDevice::new(parse_index(index)?, value)?);
+                } else if let Some(index) = k.strip_prefix("OpenCL Device ") {
+                    self.opencl_devices
+                        .push(ComputeDevice::new(parse_index(index)?, value)?);
+                } else {
                     eprintln!("discarded unknown field: {}", k);
                 }
             }
@@ -615,7 +1341,142 @@ impl FieldSetter for System {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize)]
+/// A GPU detected by the client, from a field like
+/// `"GPU 0": "Bus:1 Slot:0 Func:0 NVIDIA:7 TU116 [GeForce GTX 1660 SUPER]"`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct GpuInfo {
+    pub index: u32,
+    pub bus: i64,
+    pub slot: i64,
+    pub function: i64,
+    /// Vendor name, like "NVIDIA", "AMD" or "INTEL"
+    pub vendor: String,
+    /// GPU species that the client uses to assign work units
+    pub species: i64,
+    /// PCI vendor ID, if the client reports it
+    pub vendor_id: Option<u32>,
+    /// PCI device ID, if the client reports it
+    pub device_id: Option<u32>,
+    pub description: String,
+}
+
+impl GpuInfo {
+    fn new(index: u32, value: &str) -> Result<Self> {
+        let mut gpu = Self {
+            index,
+            ..Default::default()
+        };
+
+        // Key:value pairs are followed by the description, which may contain spaces
+        let mut rest = value.trim();
+        while let Some((k, v)) = rest
+            .split_whitespace()
+            .next()
+            .and_then(|w| w.split_once(':'))
+        {
+            match k {
+                "Bus" => gpu.bus = parse_int(v)?,
+                "Slot" => gpu.slot = parse_int(v)?,
+                "Func" => gpu.function = parse_int(v)?,
+                "Vendor" => gpu.vendor_id = Some(parse_hex(v)?),
+                "Device" => gpu.device_id = Some(parse_hex(v)?),
+                _ => match v.parse() {
+                    Ok(species) if gpu.vendor.is_empty() => {
+                        gpu.vendor = k.to_string();
+                        gpu.species = species;
+                    }
+                    _ => break,
+                },
+            }
+            rest = rest
+                .split_once(char::is_whitespace)
+                .map_or("", |(_, rest)| rest.trim_start());
+        }
+        gpu.description = rest.to_string();
+        Ok(gpu)
+    }
+}
+
+/// A CUDA or OpenCL device, from a field like
+/// `"CUDA Device 0": "Platform:0 Device:0 Bus:1 Slot:0 Compute:7.5 Driver:11.2"`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct ComputeDevice {
+    pub index: u32,
+    pub platform: i64,
+    pub device: i64,
+    pub bus: i64,
+    pub slot: i64,
+    /// Compute capability for CUDA, OpenCL version for OpenCL
+    pub compute: String,
+    pub driver: String,
+}
+
+impl ComputeDevice {
+    fn new(index: u32, value: &str) -> Result<Self> {
+        let mut device = Self {
+            index,
+            ..Default::default()
+        };
+        for (k, v) in value.split_whitespace().filter_map(|w| w.split_once(':')) {
+            match k {
+                "Platform" => device.platform = parse_int(v)?,
+                "Device" => device.device = parse_int(v)?,
+                "Bus" => device.bus = parse_int(v)?,
+                "Slot" => device.slot = parse_int(v)?,
+                "Compute" => device.compute = v.to_string(),
+                "Driver" => device.driver = v.to_string(),
+                _ => eprintln!("discarded unknown device field: {}", k),
+            }
+        }
+        Ok(device)
+    }
+}
+
+fn parse_index(s: &str) -> Result<u32> {
+    s.parse()
+        .map_err(|e| Error::UnexpectedShape(format!("{}: {}", e, s)))
+}
+
+fn parse_int(s: &str) -> Result<i64> {
+    s.parse()
+        .map_err(|e| Error::UnexpectedShape(format!("{}: {}", e, s)))
+}
+
+fn parse_hex(s: &str) -> Result<u32> {
+    u32::from_str_radix(s.trim_start_matches("0x"), 16)
+        .map_err(|e| Error::UnexpectedShape(format!("{}: {}", e, s)))
+}
+
+fn parse_bool(s: &str) -> Result<bool> {
+    Ok(s.parse::<StringBool>()?.0)
+}
+
+/// Parses sizes like "15.56GiB" into bytes.
+fn parse_bytes(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let multiplier: u64 = match unit.trim() {
+        "" | "B" => 1,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "TB" => 1_000_000_000_000,
+        _ => return Err(Error::UnexpectedShape(format!("invalid size: {}", s))),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|e| Error::UnexpectedShape(format!("{}: {}", e, s)))?;
+    Ok((number * multiplier as f64).round() as u64)
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct LibFAH {
     pub date: String,
     pub time: String,
@@ -684,6 +1545,667 @@ mod tests {
         assert_eq!(result.system.cpus, StringInt(1));
     }
 
+    #[test]
+    fn test_info_gpus() {
+        struct Test {
+            system: serde_json::Value,
+            expected_gpus: Vec<GpuInfo>,
+            expected_cuda: Vec<ComputeDevice>,
+            expected_opencl: Vec<ComputeDevice>,
+        }
+
+        let gtx_1660 = GpuInfo {
+            index: 0,
+            bus: 1,
+            slot: 0,
+            function: 0,
+            vendor: "NVIDIA".to_string(),
+            species: 7,
+            vendor_id: None,
+            device_id: None,
+            description: "TU116 [GeForce GTX 1660 SUPER]".to_string(),
+        };
+        let cuda = ComputeDevice {
+            index: 0,
+            platform: 0,
+            device: 0,
+            bus: 1,
+            slot: 0,
+            compute: "7.5".to_string(),
+            driver: "11.2".to_string(),
+        };
+        let opencl = ComputeDevice {
+            compute: "1.2".to_string(),
+            driver: "460.73".to_string(),
+            ..cuda.clone()
+        };
+
+        let tests = [
+            Test {
+                system: serde_json::json!([
+                    "System",
+                    ["GPUs", "0"],
+                    ["CUDA", "Not detected: Failed to open dynamic library 'libcuda.so'"],
+                    ["OpenCL", "Not detected: clGetPlatformIDs() returned -1001"]
+                ]),
+                expected_gpus: vec![],
+                expected_cuda: vec![],
+                expected_opencl: vec![],
+            },
+            Test {
+                system: serde_json::json!([
+                    "System",
+                    ["GPUs", "1"],
+                    [
+                        "GPU 0",
+                        "Bus:1 Slot:0 Func:0 NVIDIA:7 TU116 [GeForce GTX 1660 SUPER]"
+                    ],
+                    [
+                        "CUDA Device 0",
+                        "Platform:0 Device:0 Bus:1 Slot:0 Compute:7.5 Driver:11.2"
+                    ],
+                    [
+                        "OpenCL Device 0",
+                        "Platform:0 Device:0 Bus:1 Slot:0 Compute:1.2 Driver:460.73"
+                    ]
+                ]),
+                expected_gpus: vec![gtx_1660.clone()],
+                expected_cuda: vec![cuda.clone()],
+                expected_opencl: vec![opencl.clone()],
+            },
+            Test {
+                system: serde_json::json!([
+                    "System",
+                    ["GPUs", "2"],
+                    [
+                        "GPU 0",
+                        "Bus:1 Slot:0 Func:0 NVIDIA:7 TU116 [GeForce GTX 1660 SUPER]"
+                    ],
+                    [
+                        "GPU 1",
+                        "Bus:3 Slot:0 Func:0 Vendor:0x1002 Device:0x67df AMD:5 Ellesmere XT [Radeon RX 470/480/570/580]"
+                    ],
+                    [
+                        "CUDA Device 0",
+                        "Platform:0 Device:0 Bus:1 Slot:0 Compute:7.5 Driver:11.2"
+                    ],
+                    [
+                        "OpenCL Device 0",
+                        "Platform:0 Device:0 Bus:1 Slot:0 Compute:1.2 Driver:460.73"
+                    ],
+                    [
+                        "OpenCL Device 1",
+                        "Platform:1 Device:0 Bus:3 Slot:0 Compute:2.0 Driver:3240.7"
+                    ]
+                ]),
+                expected_gpus: vec![
+                    gtx_1660,
+                    GpuInfo {
+                        index: 1,
+                        bus: 3,
+                        slot: 0,
+                        function: 0,
+                        vendor: "AMD".to_string(),
+                        species: 5,
+                        vendor_id: Some(0x1002),
+                        device_id: Some(0x67df),
+                        description: "Ellesmere XT [Radeon RX 470/480/570/580]".to_string(),
+                    },
+                ],
+                expected_cuda: vec![cuda],
+                expected_opencl: vec![
+                    opencl,
+                    ComputeDevice {
+                        index: 1,
+                        platform: 1,
+                        device: 0,
+                        bus: 3,
+                        slot: 0,
+                        compute: "2.0".to_string(),
+                        driver: "3240.7".to_string(),
+                    },
+                ],
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let src = serde_json::from_value(serde_json::json!([
+                ["FAHClient", ["Version", "7.6.21"]],
+                ["CBang", ["Date", "Oct 20 2020"]],
+                test.system,
+                ["libFAH", ["Date", "Oct 20 2020"]]
+            ]))
+            .unwrap();
+            let system = Info::new(src).unwrap().system;
+            assert_eq!(system.gpus.0 as usize, test.expected_gpus.len(), "{}", i);
+            assert_eq!(system.gpu_devices, test.expected_gpus, "{}", i);
+            assert_eq!(system.cuda_devices, test.expected_cuda, "{}", i);
+            assert_eq!(system.opencl_devices, test.expected_opencl, "{}", i);
+        }
+    }
+
+    #[test]
+    fn test_info_system() {
+        let src = serde_json::from_value(serde_json::json!([
+            ["FAHClient", ["Version", "7.6.21"]],
+            ["CBang", ["Date", "Oct 20 2020"]],
+            [
+                "System",
+                ["Memory", "15.56GiB"],
+                ["Free Memory", "512MiB"],
+                ["Has Battery", "true"],
+                ["On Battery", "false"]
+            ],
+            ["libFAH", ["Date", "Oct 20 2020"]]
+        ]))
+        .unwrap();
+        let system = Info::new(src).unwrap().system;
+        assert_eq!(system.memory, 16707422781);
+        assert_eq!(system.free_memory, 512 << 20);
+        assert!(system.has_battery);
+        assert!(!system.on_battery);
+
+        let src = serde_json::from_value(serde_json::json!([
+            ["FAHClient"],
+            ["CBang"],
+            ["System", ["Has Battery", "yes"]],
+            ["libFAH"]
+        ]))
+        .unwrap();
+        assert!(Info::new(src).is_err());
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        struct Test {
+            s: &'static str,
+            expected: Option<u64>,
+        }
+
+        let tests = [
+            Test {
+                s: "0",
+                expected: Some(0),
+            },
+            Test {
+                s: "100B",
+                expected: Some(100),
+            },
+            Test {
+                s: "1.5KiB",
+                expected: Some(1536),
+            },
+            Test {
+                s: "2 MiB",
+                expected: Some(2 << 20),
+            },
+            Test {
+                s: "1TiB",
+                expected: Some(1 << 40),
+            },
+            Test {
+                s: "3.2GB",
+                expected: Some(3_200_000_000),
+            },
+            Test {
+                s: "1.2XB",
+                expected: None,
+            },
+            Test {
+                s: "GiB",
+                expected: None,
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            assert_eq!(parse_bytes(test.s).ok(), test.expected, "{}", i);
+        }
+    }
+
+    #[test]
+    fn test_option_key() {
+        let option = OptionKey::NextUnitPercentage(StringInt(95));
+        assert_eq!(option.key(), "next-unit-percentage");
+        assert_eq!(option.value(), "95");
+
+        // Every key is a field of Options
+        let mut options = Options::default();
+        option.apply(&mut options);
+        assert_eq!(options.next_unit_percentage, StringInt(95));
+        let value = serde_json::to_value(&options).unwrap();
+        assert_eq!(value[option.key()], "95");
+
+        assert!(OptionKey::GpuUsage(StringInt(50)).validate().is_ok());
+        assert!(OptionKey::GpuUsage(StringInt(150)).validate().is_err());
+        assert!(OptionKey::Cpus(StringInt(-1)).validate().is_err());
+    }
+
+    #[test]
+    fn test_slot_options() {
+        struct Test {
+            s: &'static str,
+            expected: Option<SlotOptions>,
+        }
+
+        let tests = [
+            Test {
+                s: "PyON 1 slot-options\n{}\n---",
+                expected: Some(SlotOptions::default()),
+            },
+            Test {
+                s: r#"PyON 1 slot-options
+{"client-type": "advanced", "cpu-usage": "80", "gpu-index": None,
+ "pause-on-start": "true", "paused": "false", "idle": "false", "cpus": "4"}
+---"#,
+                expected: Some(SlotOptions {
+                    client_type: "advanced".to_string(),
+                    cpu_usage: StringInt(80),
+                    pause_on_start: StringBool(true),
+                    extra: [("cpus", "4"), ("idle", "false")]
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect(),
+                    ..Default::default()
+                }),
+            },
+            Test {
+                s: "PyON 1 slot-options\n{\"max-units\": \"many\"}\n---",
+                expected: None,
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let options = pyon::from_message::<SlotOptions>(test.s).ok();
+            assert_eq!(options, test.expected, "{}", i);
+            if let Some(options) = options {
+                let value = serde_json::to_value(&options).unwrap();
+                assert_eq!(value["next-unit-percentage"], "0", "{}", i);
+                let round_trip: SlotOptions = serde_json::from_value(value).unwrap();
+                assert_eq!(round_trip, options, "{}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_slot_status() {
+        struct Test {
+            s: &'static str,
+            expected: SlotStatus,
+        }
+
+        let tests = [
+            Test {
+                s: "RUNNING",
+                expected: SlotStatus::Running,
+            },
+            Test {
+                s: "PAUSED",
+                expected: SlotStatus::Paused,
+            },
+            Test {
+                s: "",
+                expected: SlotStatus::Unknown(String::new()),
+            },
+            Test {
+                s: "UPLOADING",
+                expected: SlotStatus::Unknown("UPLOADING".to_string()),
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let status: SlotStatus = serde_json::from_value(serde_json::json!(test.s)).unwrap();
+            assert_eq!(status, test.expected, "{}", i);
+            assert_eq!(status.to_string(), test.s, "{}", i);
+        }
+
+        assert!(SlotStatus::Finishing.is_active());
+        assert!(!SlotStatus::Paused.is_active());
+        assert!(SlotStatus::Paused.is_paused());
+        assert_eq!(format!("{:<8}|", SlotStatus::Paused), "PAUSED  |");
+    }
+
+    #[test]
+    fn test_queue_state() {
+        struct Test {
+            s: &'static str,
+            expected: QueueState,
+        }
+
+        let tests = [
+            Test {
+                s: "DOWNLOAD",
+                expected: QueueState::Download,
+            },
+            Test {
+                s: "SEND",
+                expected: QueueState::Send,
+            },
+            Test {
+                s: "running",
+                expected: QueueState::Unknown("running".to_string()),
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let state: QueueState = serde_json::from_value(serde_json::json!(test.s)).unwrap();
+            assert_eq!(state, test.expected, "{}", i);
+            assert_eq!(state.to_string(), test.s, "{}", i);
+        }
+
+        assert!(QueueState::Running.is_active());
+        assert!(QueueState::Ready.is_paused());
+        assert!(QueueState::Send.is_transferring());
+        assert!(!QueueState::Running.is_transferring());
+    }
+
+    #[test]
+    fn test_slot_id() {
+        struct Test {
+            value: serde_json::Value,
+            expected: Option<SlotId>,
+        }
+
+        let tests = [
+            Test {
+                value: serde_json::json!("00"),
+                expected: Some(SlotId(0)),
+            },
+            Test {
+                value: serde_json::json!("12"),
+                expected: Some(SlotId(12)),
+            },
+            Test {
+                value: serde_json::json!(3),
+                expected: Some(SlotId(3)),
+            },
+            Test {
+                value: serde_json::json!("-1"),
+                expected: None,
+            },
+            Test {
+                value: serde_json::json!(""),
+                expected: None,
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let result: serde_json::Result<SlotId> = serde_json::from_value(test.value.clone());
+            assert_eq!(result.ok(), test.expected, "{}", i);
+        }
+
+        assert_eq!(SlotId(1).to_string(), "01");
+        assert_eq!(SlotId(123).to_string(), "123");
+        assert_eq!("02".parse::<QueueId>().unwrap(), QueueId(2));
+        assert_eq!(QueueId(2).to_string(), "02");
+    }
+
+    #[test]
+    fn test_percent() {
+        struct Test {
+            s: &'static str,
+            expected: Option<f64>,
+        }
+
+        let tests = [
+            Test {
+                s: "42.17%",
+                expected: Some(42.17),
+            },
+            Test {
+                s: "100%",
+                expected: Some(100.0),
+            },
+            Test {
+                s: "0.5",
+                expected: Some(0.5),
+            },
+            Test {
+                s: "%",
+                expected: None,
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let result: Result<Percent> = test.s.parse();
+            assert_eq!(result.ok().map(|p| p.0), test.expected, "{}", i);
+        }
+
+        assert_eq!(Percent(42.17).to_string(), "42.17%");
+        assert_eq!(Percent(100.0).to_string(), "100.00%");
+        assert_eq!(Percent(25.0).ratio(), 0.25);
+    }
+
+    #[test]
+    fn test_unit_id() {
+        struct Test {
+            s: &'static str,
+            expected: Option<[u8; 16]>,
+        }
+
+        let tests = [
+            Test {
+                s: "0x0000002a287234c95e8a93ff63ec35b1",
+                expected: Some([
+                    0x00, 0x00, 0x00, 0x2a, 0x28, 0x72, 0x34, 0xc9, 0x5e, 0x8a, 0x93, 0xff, 0x63,
+                    0xec, 0x35, 0xb1,
+                ]),
+            },
+            Test {
+                s: "0x0000002A287234C95E8A93FF63EC35B1",
+                expected: Some([
+                    0x00, 0x00, 0x00, 0x2a, 0x28, 0x72, 0x34, 0xc9, 0x5e, 0x8a, 0x93, 0xff, 0x63,
+                    0xec, 0x35, 0xb1,
+                ]),
+            },
+            Test {
+                s: "",
+                expected: Some([0; 16]),
+            },
+            Test {
+                s: "0000002a287234c95e8a93ff63ec35b1",
+                expected: None,
+            },
+            Test {
+                s: "0x0000002a287234c95e8a93ff63ec35",
+                expected: None,
+            },
+            Test {
+                s: "0x0000002a287234c95e8a93ff63ec35bz",
+                expected: None,
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let result: Result<UnitId> = test.s.parse();
+            assert_eq!(result.ok().map(|id| id.0), test.expected, "{}", i);
+        }
+
+        let s = "0x0000002a287234c95e8a93ff63ec35b1";
+        assert_eq!(s.parse::<UnitId>().unwrap().to_string(), s);
+    }
+
+    #[test]
+    fn test_prcg() {
+        struct Test {
+            s: &'static str,
+            expected: Option<PRCG>,
+        }
+
+        let tests = [
+            Test {
+                s: "P13424 R84 C7 G37",
+                expected: Some(PRCG {
+                    project: 13424,
+                    run: 84,
+                    clone: 7,
+                    gen: 37,
+                }),
+            },
+            Test {
+                s: " P1  R2 C3 G4 ",
+                expected: Some(PRCG {
+                    project: 1,
+                    run: 2,
+                    clone: 3,
+                    gen: 4,
+                }),
+            },
+            Test {
+                s: "P1 R2 C3",
+                expected: None,
+            },
+            Test {
+                s: "P1 R2 C3 G4 G5",
+                expected: None,
+            },
+            Test {
+                s: "R1 P2 C3 G4",
+                expected: None,
+            },
+            Test {
+                s: "P1 R2 C3 Gx",
+                expected: None,
+            },
+        ];
+
+        for (i, test) in tests.iter().enumerate() {
+            let result: Result<PRCG> = test.s.parse();
+            assert_eq!(result.ok(), test.expected, "{}", i);
+        }
+
+        let prcg: PRCG = "P13424 R84 C7 G37".parse().unwrap();
+        assert_eq!(prcg.to_string(), "P13424 R84 C7 G37");
+        assert!(prcg < "P13424 R84 C8 G0".parse().unwrap());
+        assert!(prcg > "P13423 R99 C99 G99".parse().unwrap());
+
+        let unit = SlotQueueInfo {
+            project: 13424,
+            run: 84,
+            clone: 7,
+            gen: 37,
+            ..Default::default()
+        };
+        assert_eq!(unit.prcg(), prcg);
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        fn round_trip<T>(value: &T, expected: &str)
+        where
+            T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+        {
+            let s = serde_json::to_string(value).unwrap();
+            assert_eq!(s, expected);
+            assert_eq!(&serde_json::from_str::<T>(&s).unwrap(), value, "{}", s);
+            let v = serde_json::to_value(value).unwrap();
+            assert_eq!(&serde_json::from_value::<T>(v).unwrap(), value, "{}", s);
+        }
+
+        round_trip(&StringBool(true), r#""true""#);
+        round_trip(&StringInt(-42), r#""-42""#);
+        round_trip(&Power::PowerNull, r#""""#);
+        round_trip(&Power::PowerMedium, r#""MEDIUM""#);
+        round_trip(&SlotStatus::Finishing, r#""FINISHING""#);
+        round_trip(
+            &SlotStatus::Unknown("UPLOADING".to_string()),
+            r#""UPLOADING""#,
+        );
+        round_trip(&QueueState::Send, r#""SEND""#);
+        round_trip(&SlotId(1), r#""01""#);
+        round_trip(&QueueId(12), r#""12""#);
+        round_trip(&Percent(42.17), r#""42.17%""#);
+        round_trip(&Percent(1.0 / 3.0), r#""0.3333333333333333%""#);
+        round_trip(
+            &UnitId([
+                0, 0, 0, 0x2a, 0x28, 0x72, 0x34, 0xc9, 0x5e, 0x8a, 0x93, 0xff, 0x63, 0xec, 0x35,
+                0xb1,
+            ]),
+            r#""0x0000002a287234c95e8a93ff63ec35b1""#,
+        );
+        round_trip(&FAHTime(None), r#""<invalid>""#);
+        round_trip(
+            &FAHTime::from(
+                chrono::DateTime::parse_from_rfc3339("2020-04-20T09:13:27Z")
+                    .unwrap()
+                    .with_timezone(&chrono::offset::Utc),
+            ),
+            r#""2020-04-20T09:13:27Z""#,
+        );
+        round_trip(&FAHDuration(None), r#""unknowntime""#);
+        round_trip(
+            &FAHDuration::from(chrono::Duration::seconds(3723)),
+            r#""1h 2m 3s""#,
+        );
+        round_trip(
+            &FAHDuration::from(chrono::Duration::milliseconds(95040000)),
+            r#""1day 2h 24m""#,
+        );
+
+        let server = testing::MockServer::start().unwrap();
+        let mut api =
+            API::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+
+        let options = api.options_get().unwrap();
+        let s = serde_json::to_string(&options).unwrap();
+        assert!(s.contains(r#""client-type":"#), "{}", s);
+        assert_eq!(serde_json::from_str::<Options>(&s).unwrap(), options);
+
+        let units = api.queue_info().unwrap();
+        let s = serde_json::to_string(&units).unwrap();
+        assert!(s.contains(r#""percent-done":"42.17%""#), "{}", s);
+        assert_eq!(
+            serde_json::from_str::<Vec<SlotQueueInfo>>(&s).unwrap(),
+            units
+        );
+
+        let slots = api.slot_info().unwrap();
+        let s = serde_json::to_string(&slots).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<SlotInfo>>(&s).unwrap(), slots);
+
+        let slot_options = api.slot_options_get(SlotId(0)).unwrap();
+        let s = serde_json::to_string(&slot_options).unwrap();
+        assert!(s.contains(r#""machine-id":"#), "{}", s);
+        assert_eq!(
+            serde_json::from_str::<SlotOptions>(&s).unwrap(),
+            slot_options
+        );
+
+        let simulation = api.simulation_info(SlotId(0)).unwrap();
+        let s = serde_json::to_string(&simulation).unwrap();
+        assert!(
+            s.contains(r#""start-time":"2020-04-20T09:13:27Z""#),
+            "{}",
+            s
+        );
+        assert_eq!(
+            serde_json::from_str::<SimulationInfo>(&s).unwrap(),
+            simulation
+        );
+
+        let mut info = api.info_struct().unwrap();
+        info.system.gpu_devices = vec![GpuInfo {
+            vendor_id: Some(0x10de),
+            description: "TU116 [GeForce GTX 1660 SUPER]".to_string(),
+            ..Default::default()
+        }];
+        info.system.cuda_devices = vec![ComputeDevice {
+            compute: "7.5".to_string(),
+            ..Default::default()
+        }];
+        let s = serde_json::to_string(&info).unwrap();
+        assert_eq!(serde_json::from_str::<Info>(&s).unwrap(), info);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(
+            format_duration(chrono::Duration::seconds(8000).into()),
+            "2h 13m"
+        );
+        assert_eq!(format_duration(chrono::Duration::seconds(5).into()), "5s");
+        assert_eq!(format_duration(None.into()), "unknown");
+    }
+
     #[test]
     fn test_fahduration_deserialize() {
         let s = r#""0.00 secs""#;
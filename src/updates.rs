@@ -0,0 +1,288 @@
+use super::*;
+use std::net;
+
+/// Subscribes to messages that the FAH client pushes periodically with
+/// `updates add <id> <rate> <expression>`. It owns a dedicated connection, and iterating over it
+/// returns the pushed messages as [`UpdateEvent`](./enum.UpdateEvent.html)s. `next()` blocks
+/// until the next message arrives.
+///
+/// Messages are identified by their PyON message name, not by the ID of the expression, so each
+/// expression should return a different type of message. If the connection is lost, the
+/// expressions are registered again after reconnecting. `updates clear` is sent when this is
+/// dropped.
+///
+/// Example
+/// ```no_run
+/// let mut updates = fahapi::Updates::connect_timeout(
+///     &fahapi::DEFAULT_ADDR,
+///     std::time::Duration::from_secs(1),
+/// ).unwrap();
+/// updates.add(0, std::time::Duration::from_secs(5), "$queue-info").unwrap();
+/// updates.add(1, std::time::Duration::from_secs(5), "$slot-info").unwrap();
+/// for event in updates.by_ref().take(4) {
+///     match event.unwrap() {
+///         fahapi::UpdateEvent::QueueInfo(units) => println!("{} units", units.len()),
+///         fahapi::UpdateEvent::SlotInfo(slots) => println!("{} slots", slots.len()),
+///         _ => {}
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Updates {
+    pub conn: Connection,
+    buf: Vec<u8>,
+    expressions: Vec<UpdateExpression>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct UpdateExpression {
+    id: i64,
+    rate: core::time::Duration,
+    expression: String,
+}
+
+impl UpdateExpression {
+    fn command(&self) -> String {
+        // The rate is in whole seconds
+        format!(
+            "updates add {} {} {}\n",
+            self.id,
+            self.rate.as_secs().max(1),
+            self.expression
+        )
+    }
+}
+
+impl Updates {
+    pub fn connect_timeout(addr: &net::SocketAddr, timeout: core::time::Duration) -> Result<Self> {
+        Ok(Self::from_connection(Connection::connect_timeout(
+            addr, timeout,
+        )?))
+    }
+
+    pub fn connect_with_password(
+        addr: &net::SocketAddr,
+        password: &str,
+        timeout: core::time::Duration,
+    ) -> Result<Self> {
+        Ok(Self::from_connection(Connection::connect_with_password(
+            addr, password, timeout,
+        )?))
+    }
+
+    /// The connection should not be used for other commands.
+    pub fn from_connection(conn: Connection) -> Self {
+        Self {
+            conn,
+            buf: Vec::new(),
+            expressions: Vec::new(),
+        }
+    }
+
+    /// Registers an expression, such as `$queue-info` or `$(options -a)`, which the client
+    /// evaluates and sends every `rate`. The rate is rounded down to whole seconds with a minimum
+    /// of one second. An existing expression with the same ID is replaced.
+    pub fn add(&mut self, id: i64, rate: core::time::Duration, expression: &str) -> Result<()> {
+        use std::io::Write;
+
+        if expression.is_empty() || expression.contains('\n') {
+            return Err(Error::InvalidCommand(format!(
+                "invalid expression: {:?}",
+                expression
+            )));
+        }
+
+        let expression = UpdateExpression {
+            id,
+            rate,
+            expression: expression.to_string(),
+        };
+        self.conn.conn.write_all(expression.command().as_bytes())?;
+        self.expressions.retain(|e| e.id != id);
+        self.expressions.push(expression);
+        Ok(())
+    }
+
+    /// Sends `updates del` to remove an expression.
+    pub fn del(&mut self, id: i64) -> Result<()> {
+        use std::io::Write;
+
+        self.conn
+            .conn
+            .write_all(format!("updates del {}\n", id).as_bytes())?;
+        self.expressions.retain(|e| e.id != id);
+        Ok(())
+    }
+
+    /// Sends `updates clear` to remove all expressions.
+    pub fn clear(&mut self) -> Result<()> {
+        use std::io::Write;
+
+        self.conn.conn.write_all(b"updates clear\n")?;
+        self.expressions.clear();
+        Ok(())
+    }
+
+    fn reconnect(&mut self) -> Result<()> {
+        use std::io::Write;
+
+        self.conn.reconnect()?;
+        for expression in &self.expressions {
+            self.conn.conn.write_all(expression.command().as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Updates {
+    type Item = Result<UpdateEvent>;
+
+    /// Returns None if no expressions are registered.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.expressions.is_empty() {
+            return None;
+        }
+
+        loop {
+            if let Err(e) = read_pyon_message(&mut self.conn.conn, &mut self.buf) {
+                if let Error::Disconnected = e {
+                    match self.reconnect() {
+                        Ok(()) => continue,
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+                return Some(Err(e));
+            }
+
+            return Some(
+                std::str::from_utf8(&self.buf)
+                    .map_err(Error::from)
+                    .and_then(UpdateEvent::from_message),
+            );
+        }
+    }
+}
+
+impl Drop for Updates {
+    fn drop(&mut self) {
+        if !self.expressions.is_empty() {
+            let _ = self.clear();
+        }
+    }
+}
+
+/// Message pushed by the FAH client, by PyON message name.
+#[derive(Clone, PartialEq, Debug)]
+pub enum UpdateEvent {
+    /// `units` message of `$queue-info`
+    QueueInfo(Vec<SlotQueueInfo>),
+    /// `slots` message of `$slot-info`
+    SlotInfo(Vec<SlotInfo>),
+    /// `options` message of `$(options -a)`
+    Options(Box<Options>),
+    /// `slot-options` message of `$(slot-options <slot> -a)`
+    SlotOptions(SlotOptions),
+    /// `simulation-info` message of `$(simulation-info <slot>)`
+    SimulationInfo(SimulationInfo),
+    /// `ppd` message of `$ppd`
+    Ppd(f64),
+    /// `info` message of `$info`
+    Info(Vec<Vec<serde_json::Value>>),
+    /// Any other message
+    Other {
+        name: String,
+        value: serde_json::Value,
+    },
+}
+
+impl UpdateEvent {
+    /// Parses a complete PyON message.
+    pub fn from_message(s: &str) -> Result<Self> {
+        Ok(match pyon::message_name(s)? {
+            "units" => UpdateEvent::QueueInfo(pyon::from_message(s)?),
+            "slots" => UpdateEvent::SlotInfo(pyon::from_message(s)?),
+            "options" => UpdateEvent::Options(Box::new(pyon::from_message(s)?)),
+            "slot-options" => UpdateEvent::SlotOptions(pyon::from_message(s)?),
+            "simulation-info" => UpdateEvent::SimulationInfo(pyon::from_message(s)?),
+            "ppd" => UpdateEvent::Ppd(pyon::from_message(s)?),
+            "info" => UpdateEvent::Info(pyon::from_message(s)?),
+            name => UpdateEvent::Other {
+                name: name.to_string(),
+                value: pyon::from_message(s)?,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_update_event_from_message() {
+        assert_eq!(
+            UpdateEvent::from_message("PyON 1 ppd\n1.5\n---").unwrap(),
+            UpdateEvent::Ppd(1.5)
+        );
+        assert_eq!(
+            UpdateEvent::from_message("PyON 1 units\n[{\"id\": \"01\"}]\n---").unwrap(),
+            UpdateEvent::QueueInfo(vec![SlotQueueInfo {
+                id: QueueId(1),
+                ..Default::default()
+            }])
+        );
+        assert_eq!(
+            UpdateEvent::from_message("PyON 1 heartbeat\n3\n---").unwrap(),
+            UpdateEvent::Other {
+                name: "heartbeat".to_string(),
+                value: serde_json::json!(3)
+            }
+        );
+        assert!(UpdateEvent::from_message("PyON 1 slots\n{}\n---").is_err());
+    }
+
+    #[test]
+    fn test_updates() {
+        let server = testing::MockServer::start().unwrap();
+        let mut updates =
+            Updates::connect_timeout(&server.addr(), core::time::Duration::from_secs(1)).unwrap();
+        assert!(updates.next().is_none());
+
+        let rate = core::time::Duration::from_secs(60);
+        updates.add(0, rate, "$queue-info").unwrap();
+        match updates.next().unwrap().unwrap() {
+            UpdateEvent::QueueInfo(units) => assert_eq!(units.len(), 1),
+            event => panic!("{:?}", event),
+        }
+
+        updates.add(1, rate, "$(options -a)").unwrap();
+        match updates.next().unwrap().unwrap() {
+            UpdateEvent::Options(options) => assert_eq!(options.user, "Anonymous"),
+            event => panic!("{:?}", event),
+        }
+
+        updates.del(1).unwrap();
+        // Commands are not acknowledged, so wait for the server to receive it
+        while !server.state().commands.iter().any(|c| c == "updates del 1") {
+            std::thread::yield_now();
+        }
+        server.push_updates();
+        match updates.next().unwrap().unwrap() {
+            UpdateEvent::QueueInfo(_) => {}
+            event => panic!("{:?}", event),
+        }
+
+        // Expressions are registered again after reconnecting
+        server.disconnect();
+        server.state().slots.clear();
+        match updates.next().unwrap().unwrap() {
+            UpdateEvent::QueueInfo(units) => assert!(units.is_empty()),
+            event => panic!("{:?}", event),
+        }
+
+        assert!(updates.add(2, rate, "").is_err());
+        updates.clear().unwrap();
+        assert!(updates.next().is_none());
+    }
+}